@@ -0,0 +1,37 @@
+use numerical::bspline::*;
+use numerical::interpolation::Extrapolation;
+
+fn main() {
+    println!("*** B-spline basis ***");
+    let t = vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 3.0, 3.0, 3.0];
+    println!("t = {:?}, k = 3", t);
+    println!("** result **");
+    println!("B(1.5) = {:.5?}", basis(&t, 3, 1.5));
+    let span = find_span(&t, 3, 1.5);
+    println!("B'(1.5) = {:.5?} (B_{}..B_{})", basis_ders(&t, 3, span, 1.5, 1)[1], span - 3, span);
+    println!("");
+
+    println!("*** B-spline interpolation ***");
+    let x = vec![0.0, 1.0, 1.5, 2.0, 3.0, 4.0];
+    let y = vec![2.0, 4.0, 3.0, 1.0, 2.0, 0.5];
+    println!("x = {:?}", x);
+    println!("y = {:?}", y);
+    println!("** result **");
+    for k in 1..6 {
+        let sp: BSpline<f64> = BSpline::interpolate(&x, &y, k);
+        println!("k = {}: f(1.75) = {:.5}, f'(1.75) = {:.5}", k, sp.calc(1.75).unwrap(), sp.derivative().calc(1.75).unwrap());
+    }
+    println!("");
+
+    println!("*** Least-square B-spline fitting ***");
+    let x: Vec<f64> = (0..101).map(|i| i as f64 * 0.1).collect();
+    let y: Vec<f64> = x.iter().map(|&xi| xi.sin() + 0.1 * (37.0 * xi).sin()).collect();
+    let t = uniform_knots(0.0, 10.0, 8, 3);
+    let mut fit: BSpline<f64> = BSpline::fit(&x, &y, &t, 3);
+    println!("y = sin(x) + 0.1 sin(37x), x = [0.0, 0.1, ..., 10.0]");
+    println!("cubic, 8 uniform intervals");
+    println!("** result **");
+    println!("f(2) = {:.5} (sin(2) = {:.5})", fit.calc(2.0).unwrap(), 2f64.sin());
+    fit.ext = Extrapolation::Linear;
+    println!("f(10.5) = {:.5} (linear extrapolation)", fit.calc(10.5).unwrap());
+}
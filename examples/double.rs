@@ -20,4 +20,8 @@ fn main() {
 
     println!("** Double Exponential Formula (n = 20, h = 0.1) **");
     println!("n = 20: I = {:.6}\n", i.def((0.0, 2.0), (|_x: f64| 1.0, |x: f64| 1.0 + 0.5 * x), 20, 0.1));
+
+    println!("** Adaptive Gauss-Kronrod (tol = (1e-10, 1e-12)) **");
+    let (v, e) = i.gauss_kronrod((0.0, 2.0), (|_x: f64| 1.0, |x: f64| 1.0 + 0.5 * x), (1e-10, 1e-12));
+    println!("I = {:.10}, error = {:.3e}\n", v, e);
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 use numerical::fit::*;
+use numerical::interpolation::Extrapolation;
+use ndarray::*;
 
 fn main() {
     println!("*** 3D spline curve fitting ***");
@@ -12,6 +14,90 @@ fn main() {
     println!("f(1.75) = {:.5}", sp.calc(1.75).unwrap());
     println!("");
 
+    let x = vec![0.0, 1.0, 1.5, 2.0, 3.0];
+    let y = vec![2.0, 4.0, 3.0, 1.0, 2.0];
+    let natural: Spline3d<f64> = Spline3d::with_boundary(&x, &y, SplineBoundary::Natural);
+    let not_a_knot: Spline3d<f64> = Spline3d::with_boundary(&x, &y, SplineBoundary::NotAKnot);
+    let periodic: Spline3d<f64> = Spline3d::with_boundary(&x, &y, SplineBoundary::Periodic);
+    println!("** natural / not-a-knot / periodic **");
+    println!("f(1.75) = {:.5} / {:.5} / {:.5}", natural.calc(1.75).unwrap(), not_a_knot.calc(1.75).unwrap(), periodic.calc(1.75).unwrap());
+    println!("");
+
+    println!("*** Spline derivatives, integral and roots ***");
+    let x: Vec<f64> = (0..21).map(|i| i as f64 * 0.5).collect();
+    let y: Vec<f64> = x.iter().map(|&xi| xi.sin()).collect();
+    let sp: Spline3d<f64> = Spline3d::with_boundary(&x, &y, SplineBoundary::NotAKnot);
+    println!("y = sin(x), x = [0.0, 0.5, ..., 10.0]");
+    println!("** result **");
+    println!("f'(1)  = {:.5} (exact {:.5})", sp.derivative(1.0).unwrap(), 1f64.cos());
+    println!("f''(1) = {:.5} (exact {:.5})", sp.second_derivative(1.0).unwrap(), -1f64.sin());
+    println!("int_0^pi f(x) dx = {:.5} (exact 2)", sp.integral(0.0, std::f64::consts::PI).unwrap());
+    println!("F(2) = {:.5} (exact {:.5})", sp.antiderivative().calc(2.0).unwrap(), 1.0 - 2f64.cos());
+    println!("f(x) = 0.5 at x = {:.5?}", sp.solve(0.5));
+    println!("");
+
+    println!("*** Evaluation on arrays ***");
+    let z: Array1<f64> = Array::linspace(0.0, 10.0, 1_000_001);
+    let fz = sp.calc_sorted(&z).unwrap();
+    let grid = arr2(&[[0.5, 1.5], [2.5, 3.5]]);
+    println!("f on 1000001 sorted points, f(5) = {:.5}", fz[500_000]);
+    println!("f([[0.5, 1.5], [2.5, 3.5]]) = {:.5}", sp.calc_array(&grid).unwrap());
+    println!("");
+
+    println!("*** Unsorted knots and extrapolation ***");
+    let mut sp: Spline3d<f64> = Spline3d::with_boundary(&vec![3.0, 0.0, 2.0, 1.0],
+                                                        &vec![24.0, 0.0, 6.0, 0.0],
+                                                        SplineBoundary::NotAKnot);
+    println!("x = [3.0, 0.0, 2.0, 1.0]");
+    println!("y = [24.0, 0.0, 6.0, 0.0]");
+    println!("** result **");
+    println!("f(1.5) = {:.5}", sp.calc(1.5).unwrap());
+    println!("f(4) = {:?} (Error)", sp.calc(4.0));
+    for &ext in [Extrapolation::Clamp, Extrapolation::Linear, Extrapolation::Polynomial, Extrapolation::Periodic].iter() {
+        sp.ext = ext;
+        println!("f(4) = {:.5} ({:?})", sp.calc(4.0).unwrap(), ext);
+    }
+    println!("");
+
+    println!("*** Smoothing spline ***");
+    let x: Vec<f64> = (0..41).map(|i| i as f64 * 0.25).collect();
+    let noise = |i: usize| ((i * i * 37 + 11) % 23) as f64 / 55.0 - 0.2;
+    let y: Vec<f64> = x.iter().enumerate().map(|(i, &xi)| xi.sin() + noise(i)).collect();
+    let fixed: SmoothingSpline<f64> = SmoothingSpline::new(&x, &y, 0.1);
+    let auto: SmoothingSpline<f64> = SmoothingSpline::gcv(&x, &y, &vec![1.0; x.len()]);
+    println!("y = sin(x) + noise in [-0.2, 0.2], x = [0.0, 0.25, ..., 10.0]");
+    println!("** result **");
+    println!("lambda = 0.1: f(2) = {:.5}, edf = {:.3}", fixed.calc(2.0).unwrap(), fixed.edf);
+    println!("GCV lambda = {:.5}: f(2) = {:.5}, edf = {:.3}", auto.lambda, auto.calc(2.0).unwrap(), auto.edf);
+    println!("sin(2) = {:.5}", 2f64.sin());
+    println!("");
+
+    println!("*** Piecewise cubic Hermite interpolation ***");
+    let ch: CubicHermite<f64> = CubicHermite::new(&vec![0.0, 1.0, 1.5, 2.0, 3.0],
+                                                  &vec![2.0, 4.0, 3.0, 1.0, 2.0],
+                                                  &vec![5.0, 0.0, -3.0, 0.0, 3.0]);
+    println!("x  = [0.0, 1.0, 1.5, 2.0, 3.0]");
+    println!("y  = [2.0, 4.0, 3.0, 1.0, 2.0]");
+    println!("dy = [5.0, 0.0, -3.0, 0.0, 3.0]");
+    println!("** result **");
+    println!("f(1.75) = {:.5}", ch.calc(1.75).unwrap());
+    println!("");
+
+    println!("*** Shape-preserving interpolation ***");
+    let x = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+    let y = vec![0.0, 0.0, 0.1, 0.9, 1.0, 1.0];
+    let pchip: CubicHermite<f64> = CubicHermite::pchip(&x, &y);
+    let akima: CubicHermite<f64> = CubicHermite::akima(&x, &y);
+    let steffen: CubicHermite<f64> = CubicHermite::steffen(&x, &y);
+    println!("x = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]");
+    println!("y = [0.0, 0.0, 0.1, 0.9, 1.0, 1.0]");
+    println!("** result **");
+    for &z in [0.5, 2.5, 4.5].iter() {
+        println!("f({}) = {:.5} (PCHIP), {:.5} (Akima), {:.5} (Steffen)",
+                 z, pchip.calc(z).unwrap(), akima.calc(z).unwrap(), steffen.calc(z).unwrap());
+    }
+    println!("");
+
     println!("*** Least-square method fitting ***");
     let lsm: LSM<f64> = LSM::new(&vec![ 0.2,  0.5,  1.0,  2.0,  4.0,  8.0, 10.0],
                                  &vec![12.1,  4.9,  2.9,  2.1,  2.1,  3.4,  4.3],
@@ -24,4 +110,78 @@ fn main() {
     println!("a = {:.5}", lsm.coeff[0]);
     println!("b = {:.5}", lsm.coeff[1]);
     println!("f(6) = {:.5}", lsm.calc(6.0));
-}
\ No newline at end of file
+    println!("rank = {}, cond = {:.5}", lsm.rank, lsm.cond());
+    println!("");
+
+    println!("*** Ill-conditioned polynomial basis ***");
+    let x: Vec<f64> = (0..51).map(|i| i as f64 * 0.2).collect();
+    let y: Vec<f64> = x.iter().map(|&xi| (0.5 * xi).sin()).collect();
+    let items: Vec<fn(f64) -> f64> = vec![|_| 1.0, |x| x, |x| x.powi(2), |x| x.powi(3), |x| x.powi(4), |x| x.powi(5),
+                                          |x| x.powi(6), |x| x.powi(7), |x| x.powi(8), |x| x.powi(9), |x| x.powi(10)];
+    let lsm: LSM<f64> = LSM::new(&x, &y, items);
+    println!("y = sin(x / 2), x = [0.0, 0.2, ..., 10.0], items 1, x, ..., x^10");
+    println!("** result **");
+    println!("rank = {}, cond = {:.3e}", lsm.rank, lsm.cond());
+    println!("f(5.1) = {:.8} (exact {:.8})", lsm.calc(5.1), (2.55f64).sin());
+    let dependent: LSM<f64> = LSM::new(&x, &y, vec![|x: f64| x, |x: f64| 2.0 * x]);
+    println!("items x, 2x: rank = {}, rank deficient = {}", dependent.rank, dependent.rank_deficient());
+    println!("");
+
+    println!("*** Weighted and regularized least squares ***");
+    let x: Vec<f64> = (0..41).map(|i| i as f64 * 0.025).collect();
+    let noise = |i: usize| ((i * i * 37 + 11) % 23) as f64 / 115.0 - 0.1;
+    let y: Vec<f64> = x.iter().enumerate().map(|(i, &xi)| (3.0 * xi).sin() + noise(i)).collect();
+    let w: Vec<f64> = x.iter().map(|&xi| if xi < 0.5 { 1.0 } else { 4.0 }).collect();
+    let items: Vec<fn(f64) -> f64> = vec![|_| 1.0, |x| x, |x| x.powi(2), |x| x.powi(3), |x| x.powi(4), |x| x.powi(5),
+                                          |x| x.powi(6), |x| x.powi(7), |x| x.powi(8), |x| x.powi(9)];
+    println!("y = sin(3x) + noise in [-0.1, 0.1], x = [0.0, 0.025, ..., 1.0], sigma = 1 (x < 0.5), 0.5 (x >= 0.5)");
+    println!("items 1, x, ..., x^9");
+    println!("** result **");
+    let weighted: LSM<f64> = LSM::with_weights(&x, &y, &w, items.clone());
+    let ridge: LSM<f64> = LSM::ridge(&x, &y, &w, items.clone(), 1e-3, None);
+    // penalize the differences of consecutive coefficients
+    let l = Array2::from_shape_fn((9, 10), |(i, j)| if j == i { -1.0 } else if j == i + 1 { 1.0 } else { 0.0 });
+    let gcv: LSM<f64> = LSM::gcv(&x, &y, &w, items.clone(), Some(&l));
+    let lcurve: LSM<f64> = LSM::lcurve(&x, &y, &w, items.clone(), None);
+    println!("sin(2.1) = {:.5}", 2.1f64.sin());
+    println!("weighted:                   f(0.7) = {:.5}, edf = {:.3}", weighted.calc(0.7), weighted.edf);
+    println!("ridge lambda = 0.001:       f(0.7) = {:.5}, edf = {:.3}", ridge.calc(0.7), ridge.edf);
+    println!("GCV lambda = {:.3e}:     f(0.7) = {:.5}, edf = {:.3}", gcv.lambda, gcv.calc(0.7), gcv.edf);
+    println!("L-curve lambda = {:.3e}: f(0.7) = {:.5}, edf = {:.3}", lcurve.lambda, lcurve.calc(0.7), lcurve.edf);
+    println!("");
+
+    println!("*** Fit statistics ***");
+    let x = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+    let y = vec![2.1, 3.9, 6.2, 7.8, 10.1, 12.2, 13.8];
+    let lsm: LSM<f64> = LSM::new(&x, &y, vec![|_| 1.0, |x| x]);
+    let st = &lsm.stats;
+    println!("x = {:?}", x);
+    println!("y = {:?}", y);
+    println!("** equation **");
+    println!("y = a + bx");
+    println!("** result **");
+    println!("a = {:.5} +- {:.5}", lsm.coeff[0], st.std_errors[0]);
+    println!("b = {:.5} +- {:.5}", lsm.coeff[1], st.std_errors[1]);
+    println!("residuals = {:.3?}", st.residuals);
+    println!("RSS = {:.5}, R^2 = {:.5}, adjusted R^2 = {:.5}, reduced chi^2 = {:.5}", st.rss, st.r2, st.adj_r2, st.reduced_chi2);
+    println!("AIC = {:.5}, BIC = {:.5}", st.aic, st.bic);
+    println!("95% confidence interval of f(4.5): {:.5?}", lsm.confidence_interval(4.5, 0.95).unwrap());
+    println!("95% prediction interval of y(4.5): {:.5?}", lsm.prediction_interval(4.5, 0.95).unwrap());
+    println!("");
+
+    println!("*** Robust regression ***");
+    let x: Vec<f64> = (0..20).map(|i| i as f64).collect();
+    let mut y: Vec<f64> = x.iter().enumerate().map(|(i, &xi)| 2.0 + 0.5 * xi + 0.01 * ((i * 7 % 5) as f64 - 2.0)).collect();
+    y[4] += 8.0;
+    y[13] -= 6.0;
+    let items: Vec<fn(f64) -> f64> = vec![|_| 1.0, |x| x];
+    println!("y = 2 + 0.5x + small noise, x = [0, 1, ..., 19], outliers at x = 4 and 13");
+    println!("** result **");
+    let ols: LSM<f64> = LSM::new(&x, &y, items.clone());
+    println!("least squares: a = {:.5}, b = {:.5}", ols.coeff[0], ols.coeff[1]);
+    for &loss in [RobustLoss::huber(), RobustLoss::tukey(), RobustLoss::cauchy()].iter() {
+        let robust: RobustLSM<f64> = RobustLSM::new(&x, &y, items.clone(), loss);
+        println!("{:?}: a = {:.5}, b = {:.5}, scale = {:.5}, outliers = {:?}",
+                 loss, robust.lsm.coeff[0], robust.lsm.coeff[1], robust.scale, robust.outliers(0.5));
+    }
+}
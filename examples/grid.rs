@@ -0,0 +1,36 @@
+use numerical::grid::*;
+use numerical::interpolation::Extrapolation;
+use ndarray::*;
+
+fn main() {
+    println!("*** 2D gridded interpolation ***");
+    let t = vec![300.0, 350.0, 400.0, 450.0, 500.0];
+    let p = vec![1.0, 2.0, 5.0, 10.0];
+    // ideal gas density [kg/m^3] of air, rho = p / (R T) with p in bar
+    let rho = Array2::from_shape_fn((5, 4), |(i, j)| p[j] * 1e5 / (287.05 * t[i]));
+    println!("T = {:?}", t);
+    println!("p = {:?}", p);
+    println!("** result **");
+    for &m in [GridMethod::Linear, GridMethod::CubicConvolution, GridMethod::Spline].iter() {
+        let g: Grid2d<f64> = Grid2d::new(&t, &p, &rho, m);
+        println!("rho(425, 3) = {:.5} ({:?})", g.calc(425.0, 3.0).unwrap(), m);
+    }
+    println!("exact          {:.5}", 3e5 / (287.05 * 425.0));
+    let mut g: Grid2d<f64> = Grid2d::new(&t, &p, &rho, GridMethod::Spline);
+    println!("rho(550, 3) = {:?}", g.calc(550.0, 3.0));
+    g.ext = [Extrapolation::Linear, Extrapolation::Error];
+    println!("rho(550, 3) = {:.5} (linear extrapolation in T)", g.calc(550.0, 3.0).unwrap());
+    let tt = arr2(&[[310.0, 320.0], [330.0, 340.0]]);
+    let pp = arr2(&[[1.5, 1.5], [1.5, 1.5]]);
+    println!("rho(T, 1.5) = {:.5}", g.calc_array(&tt, &pp).unwrap());
+    println!("");
+
+    println!("*** 3D gridded interpolation ***");
+    let x: Vec<f64> = (0..11).map(|i| i as f64 * 0.1).collect();
+    let v = Array3::from_shape_fn((11, 11, 11), |(i, j, k)| (x[i] + 2.0 * x[j]).sin() * (-x[k]).exp());
+    let g: Grid3d<f64> = Grid3d::new(&x, &x, &x, &v, GridMethod::Spline);
+    println!("f(x, y, z) = sin(x + 2y) exp(-z) on 11 x 11 x 11 points in [0, 1]^3");
+    println!("** result **");
+    println!("f(0.35, 0.62, 0.18) = {:.6} (exact {:.6})", g.calc(0.35, 0.62, 0.18).unwrap(),
+             (0.35f64 + 1.24).sin() * (-0.18f64).exp());
+}
@@ -18,5 +18,9 @@ fn main() {
     println!("n = 10: I = {:.6}\n", i.chebyshev(0.0, 1.0, 10));
 
     println!("** Double Exponential Formula (n = 20, h = 0.1) **");
-    println!("n = 20: I = {:.6}", i.def(0.0, 1.0, 20, 0.1));
+    println!("n = 20: I = {:.6}\n", i.def(0.0, 1.0, 20, 0.1));
+
+    println!("** Adaptive Gauss-Kronrod (tol = 1e-10) **");
+    let (v, e) = i.gauss_kronrod(0.0, 1.0, 1e-10);
+    println!("I = {:.10}, error = {:.3e}", v, e);
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
 use numerical::interpolation::*;
+use numerical::polynomial::zeta_vec;
 
 fn main() {
     println!("*** Lagrange interpolation ***");
     let i: LagrangeItp<f64> = LagrangeItp::new(&vec![   0.5,    1.0,    1.5,    2.0], 
-                                               &vec![0.3734, 0.5104, 0.4712, 0.3345]);
+                                               &vec![0.3734, 0.5104, 0.4712, 0.3345]).unwrap();
     println!("x = [   0.5,    1.0,    1.5,    2.0]");
     println!("y = [0.3734, 0.5104, 0.4712, 0.3345]");
     println!("f(0.5) = {:.5}", i.calc(0.5));
@@ -14,9 +15,9 @@ fn main() {
     println!("");
 
     let mut i: LagrangeItp<f64> = LagrangeItp::new(&vec![   0.5,    1.5], 
-                                                   &vec![0.3734, 0.4712]);
+                                                   &vec![0.3734, 0.4712]).unwrap();
     i.push_vec(&vec![   1.0,    2.0], 
-               &vec![0.5104, 0.3345]);
+               &vec![0.5104, 0.3345]).unwrap();
     println!("x = [   0.5,    1.5,    1.0,    2.0]");
     println!("y = [0.3734, 0.4712, 0.5104, 0.3345]");
     println!("f(0.5) = {:.5}", i.calc(0.5));
@@ -26,36 +27,141 @@ fn main() {
     println!("f(0.8) = {:.5}", i.calc(0.8));
     println!("");
 
+    println!("*** Barycentric Lagrange interpolation ***");
+    let mut i: BaryLagrangeItp<f64> = BaryLagrangeItp::new(&vec![   0.5,    1.5], 
+                                                           &vec![0.3734, 0.4712]).unwrap();
+    i.push_vec(&vec![   1.0,    2.0], 
+               &vec![0.5104, 0.3345]).unwrap();
+    println!("x = [   0.5,    1.5,    1.0,    2.0]");
+    println!("y = [0.3734, 0.4712, 0.5104, 0.3345]");
+    println!("f(0.8) = {:.5}", i.calc(0.8));
+    println!("");
+
+    let y: Vec<f64> = zeta_vec(40).iter().map(|&z: &f64| 1.0 / (1.0 + 25.0 * z * z)).collect();
+    let i: BaryLagrangeItp<f64> = BaryLagrangeItp::chebyshev(-1.0, 1.0, &y).unwrap();
+    println!("f(x) = 1 / (1 + 25x^2) at 41 Chebyshev points");
+    println!("f(0.3) = {:.5} (exact: {:.5})", i.calc(0.3), 1.0 / (1.0 + 25.0 * 0.09));
+    println!("");
+
     println!("*** Newton's Divided Difference interpolation ***");
     let i: NewtonDivItp<f64> = NewtonDivItp::new(&vec![   0.2,    0.5,    1.0,    1.5,    2.0,    3.0], 
-                                                 &vec![0.0793, 0.1915, 0.3413, 0.4332, 0.4772, 0.4987]);
+                                                 &vec![0.0793, 0.1915, 0.3413, 0.4332, 0.4772, 0.4987]).unwrap();
     println!("x = [   0.2,    0.5,    1.0,    1.5,    2.0,    3.0]");
     println!("y = [0.0793, 0.1915, 0.3413, 0.4332, 0.4772, 0.4987]");
     println!("f(0.7) = {:.5}", i.calc(0.7));
     println!("");
 
     let mut i: NewtonDivItp<f64> = NewtonDivItp::new(&vec![   0.2,    1.0,    2.0], 
-                                                     &vec![0.0793, 0.3413, 0.4772]);
+                                                     &vec![0.0793, 0.3413, 0.4772]).unwrap();
     i.push_vec(&vec![   0.5,    1.5,    3.0], 
-               &vec![0.1915, 0.4332, 0.4987]);
+               &vec![0.1915, 0.4332, 0.4987]).unwrap();
     println!("x = [   0.2,    1.0,    2.0,    0.5,    1.5,    3.0]");
     println!("y = [0.0793, 0.3413, 0.4772, 0.1915, 0.4332, 0.4987]");
     println!("f(0.7) = {:.5}", i.calc(0.7));
     println!("");
 
+    println!("*** Neville-Aitken interpolation ***");
+    let i: NevilleItp<f64> = NevilleItp::new(&vec![   0.2,    0.5,    1.0,    1.5,    2.0,    3.0], 
+                                             &vec![0.0793, 0.1915, 0.3413, 0.4332, 0.4772, 0.4987]).unwrap();
+    println!("x = [   0.2,    0.5,    1.0,    1.5,    2.0,    3.0]");
+    println!("y = [0.0793, 0.1915, 0.3413, 0.4332, 0.4772, 0.4987]");
+    let (v, e) = i.calc_with_error(0.7);
+    println!("f(0.7) = {:.5} (error estimate: {:.5})", v, e);
+
+    let i: NevilleItp<f64> = NevilleItp::with_nearest(&vec![   0.2,    0.5,    1.0,    1.5,    2.0,    3.0], 
+                                                      &vec![0.0793, 0.1915, 0.3413, 0.4332, 0.4772, 0.4987], 3).unwrap();
+    let (v, e) = i.calc_with_error(0.7);
+    println!("3 nearest points: f(0.7) = {:.5} (error estimate: {:.5})", v, e);
+    println!("");
+
+    println!("*** Hermite interpolation ***");
+    let i: HermiteItp<f64> = HermiteItp::new(&vec![   0.0,    0.5,    1.0], 
+                                             &vec![0.0000, 0.4794, 0.8415], 
+                                             &vec![1.0000, 0.8776, 0.5403]).unwrap();
+    println!("x  = [   0.0,    0.5,    1.0]");
+    println!("y  = [0.0000, 0.4794, 0.8415]");
+    println!("dy = [1.0000, 0.8776, 0.5403]");
+    println!("f(0.8) = {:.5}", i.calc(0.8));
+    println!("");
+
     println!("*** Newton's Forward Difference interpolation ***");
     let i: NewtonForItp<f64> = NewtonForItp::new(&vec![   0.0,    0.5,    1.0,    1.5,    2.0], 
-                                                 &vec![0.0000, 0.1014, 0.6931, 2.0617, 4.3944]);
+                                                 &vec![0.0000, 0.1014, 0.6931, 2.0617, 4.3944]).unwrap();
     println!("x = [   0.0,    0.5,    1.0,    1.5,    2.0]");
     println!("y = [0.0000, 0.1014, 0.6931, 2.0617, 4.3944]");
     println!("f(0.8) = {:.5}", i.calc(0.8));
     println!("");
 
     let mut i: NewtonForItp<f64> = NewtonForItp::new(&vec![   0.0,    0.5,    1.0], 
-                                                     &vec![0.0000, 0.1014, 0.6931]);
+                                                     &vec![0.0000, 0.1014, 0.6931]).unwrap();
     i.push_vec(&vec![   1.5,    2.0], 
-               &vec![2.0617, 4.3944]);
+               &vec![2.0617, 4.3944]).unwrap();
     println!("x = [   0.0,    0.5,    1.0,    1.5,    2.0]");
     println!("y = [0.0000, 0.1014, 0.6931, 2.0617, 4.3944]");
     println!("f(0.8) = {:.5}", i.calc(0.8));
-}
\ No newline at end of file
+    println!("");
+
+    println!("*** Backward and central difference interpolation ***");
+    let x: Vec<f64> = (0..21).map(|j| 1.0 + 0.1 * j as f64).collect();
+    let y: Vec<f64> = x.iter().map(|&xj| xj.ln()).collect();
+    let i: NewtonForItp<f64> = NewtonForItp::new(&x, &y).unwrap();
+    println!("y = ln(x), x = [1.0, 1.1, ..., 3.0], 4 differences");
+    for &z in [2.04, 2.09, 2.96].iter() {
+        println!("f({}): Gauss forward {:.8}, Stirling {:.8}, Bessel {:.8}, auto {:.8}, exact {:.8}",
+                 z, i.gauss_forward(z, 4), i.stirling(z, 4), i.bessel(z, 4), i.calc_auto(z, 4), z.ln());
+    }
+    println!("f(2.96): backward (all differences) {:.8}", i.calc_backward(2.96));
+    println!("");
+
+    println!("*** Rational interpolation ***");
+    let x: Vec<f64> = (0..41).map(|j| -1.0 + j as f64 / 20.0).collect();
+    let y: Vec<f64> = x.iter().map(|&xj| 1.0 / (1.0 + 25.0 * xj * xj)).collect();
+    let poly: BaryLagrangeItp<f64> = BaryLagrangeItp::new(&x, &y).unwrap();
+    let fh: FloaterHormannItp<f64> = FloaterHormannItp::with_d(&x, &y, 3).unwrap();
+    let aaa: AaaItp<f64> = AaaItp::new(&x, &y).unwrap();
+    println!("f(x) = 1 / (1 + 25x^2) at 41 equispaced points");
+    println!("f(0.96), exact:        {:.5}", 1.0 / (1.0 + 25.0 * 0.9216));
+    println!("polynomial:            {:.5}", poly.calc(0.96));
+    println!("Floater-Hormann d = 3: {:.5}", fh.calc(0.96));
+    println!("AAA ({} support points): {:.5}", aaa.z.len(), aaa.calc(0.96));
+    println!("");
+
+    println!("*** Trigonometric interpolation ***");
+    let n = 16;
+    let period = 24.0;
+    // daily cycle sampled every 1.5 hours
+    let x: Vec<f64> = (0..n).map(|j| j as f64 * period / n as f64).collect();
+    let y: Vec<f64> = x.iter().map(|&t| 15.0 - 5.0 * (std::f64::consts::PI * t / 12.0).cos() + (std::f64::consts::PI * t / 6.0).sin()).collect();
+    let trig: TrigItp<f64> = TrigItp::new(&x, &y).unwrap();
+    println!("f(t) = 15 - 5cos(pi t / 12) + sin(pi t / 6) at t = 0, 1.5, ..., 22.5");
+    println!("f(10) = {:.5}, f(34) = {:.5}, exact {:.5}", trig.calc(10.0), trig.calc(34.0),
+             15.0 - 5.0 * (std::f64::consts::PI * 10.0 / 12.0).cos() + (std::f64::consts::PI * 10.0 / 6.0).sin());
+    println!("f'(10) = {:.5}, f''(10) = {:.5}", trig.derivative(10.0, 1), trig.derivative(10.0, 2));
+    println!("hourly: {:.3?}", trig.resample(24).unwrap());
+    println!("");
+
+    println!("*** Sliding window ***");
+    let feed = |t: f64| (0.3 * t).sin() + 0.05 * t;
+    let x: Vec<f64> = (0..4).map(|t| t as f64).collect();
+    let y: Vec<f64> = x.iter().map(|&t| feed(t)).collect();
+    let mut window: SlidingItp<NewtonDivItp<f64>> = SlidingItp::new(&x, &y, 4).unwrap();
+    for t in 4..100 {
+        window.push(t as f64, feed(t as f64)).unwrap();
+    }
+    println!("f(t) = sin(0.3t) + 0.05t fed at t = 0, 1, ..., 99, window of 4 points");
+    println!("window x = {:?}", window.itp.x);
+    println!("f(98.5) = {:.5}, exact {:.5}", window.calc(98.5), feed(98.5));
+    let mut itp: NewtonDivItp<f64> = NewtonDivItp::new(&vec![0.0, 1.0, 2.0, 3.0], &vec![1.0, 2.0, 6.0, 10.0]).unwrap();
+    println!("remove(2) = {:?}, f(2) = {:.5} without (2, 6)", itp.remove(2).unwrap(), itp.calc(2.0));
+    println!("");
+
+    println!("*** Invalid input ***");
+    match NewtonDivItp::<f64>::new(&vec![0.0, 1.0, 1.0], &vec![0.0, 1.0, 2.0]) {
+        Ok(_) => println!("accepted"),
+        Err(e) => println!("rejected: {}", e),
+    }
+    match NewtonForItp::<f64>::with_rtol(&vec![0.0, 0.5, 1.01], &vec![0.0, 1.0, 2.0], 1e-3) {
+        Ok(_) => println!("accepted"),
+        Err(e) => println!("rejected: {}", e),
+    }
+}
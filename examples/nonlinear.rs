@@ -0,0 +1,37 @@
+use numerical::nonlinear::*;
+
+fn main() {
+    println!("*** Levenberg-Marquardt: exponential rise ***");
+    // NIST StRD Misra1a
+    let x = vec![77.6, 114.9, 141.1, 190.8, 239.9, 289.0, 332.8, 378.4, 434.8, 477.3, 536.8, 593.1, 689.1, 760.0];
+    let y = vec![10.07, 14.73, 17.94, 23.93, 29.61, 35.18, 40.02, 44.82, 50.76, 55.05, 61.01, 66.40, 75.47, 81.78];
+    let w = vec![1.0; x.len()];
+    let mut lm: LevenbergMarquardt<f64> = LevenbergMarquardt::new(|x, p| p[0] * (1.0 - (-p[1] * x).exp()));
+    lm.jacobian = Some(|x, p| vec![1.0 - (-p[1] * x).exp(), p[0] * x * (-p[1] * x).exp()]);
+    let fit = lm.fit(&x, &y, &w, &vec![500.0, 1e-4]);
+    println!("** equation **");
+    println!("y = a (1 - exp(-bx)), start a = 500, b = 1e-4");
+    println!("** result **");
+    println!("a = {:.5} +- {:.5} (certified 238.94213 +- 2.70701)", fit.params[0], fit.stats.std_errors[0]);
+    println!("b = {:.5e} +- {:.5e} (certified 5.50156e-4 +- 7.26687e-6)", fit.params[1], fit.stats.std_errors[1]);
+    println!("RSS = {:.5}, R^2 = {:.5}, {} iterations", fit.stats.rss, fit.stats.r2, fit.iterations);
+    println!("95% confidence interval of f(500): {:.5?}", fit.confidence_interval(500.0, 0.95).unwrap());
+    println!("");
+
+    println!("*** Levenberg-Marquardt: Lorentzian with bounds ***");
+    let lorentz = |x: f64, p: &Vec<f64>| p[0] / (1.0 + ((x - p[1]) / p[2]).powi(2)) + p[3];
+    let x: Vec<f64> = (0..101).map(|i| -5.0 + i as f64 * 0.1).collect();
+    let y: Vec<f64> = x.iter().enumerate()
+        .map(|(i, &xi)| lorentz(xi, &vec![3.0, 0.7, 0.4, 0.2]) + 0.002 * (((i * 37) % 11) as f64 - 5.0)).collect();
+    let w = vec![1.0; x.len()];
+    let mut lm: LevenbergMarquardt<f64> = LevenbergMarquardt::new(lorentz);
+    lm.bounds = vec![(0.0, 10.0), (-5.0, 5.0), (0.01, 5.0), (-1.0, 1.0)];
+    lm.geodesic = true;
+    let fit = lm.fit(&x, &y, &w, &vec![1.0, 0.0, 1.0, 0.0]);
+    println!("y = A / (1 + ((x - x0) / g)^2) + c, true A = 3, x0 = 0.7, g = 0.4, c = 0.2");
+    println!("** result **");
+    for (name, (p, e)) in ["A", "x0", "g", "c"].iter().zip(fit.params.iter().zip(fit.stats.std_errors.iter())) {
+        println!("{} = {:.5} +- {:.5}", name, p, e);
+    }
+    println!("reduced chi^2 = {:.3e}, converged = {}", fit.stats.reduced_chi2, fit.converged);
+}
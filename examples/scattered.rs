@@ -0,0 +1,35 @@
+use numerical::scattered::*;
+use ndarray::*;
+
+fn main() {
+    // Franke-like test function sampled on scattered points
+    let f = |x: f64, y: f64| (-((x - 0.3) * (x - 0.3) + (y - 0.6) * (y - 0.6)) * 5.0).exp() + 0.5 * x * y;
+    let points = arr2(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0], [0.5, 0.5],
+                        [0.2, 0.7], [0.8, 0.3], [0.3, 0.2], [0.7, 0.9], [0.1, 0.4],
+                        [0.6, 0.1], [0.9, 0.6], [0.4, 0.8], [0.35, 0.45], [0.65, 0.6]]);
+    let values: Array1<f64> = points.outer_iter().map(|p| f(p[0], p[1])).collect();
+    let (x, y) = (0.3, 0.55);
+
+    println!("*** Radial basis function interpolation ***");
+    println!("f(x, y) = exp(-5((x - 0.3)^2 + (y - 0.6)^2)) + xy / 2 at 15 points");
+    println!("** result **");
+    for &k in [RbfKernel::Gaussian(3.0), RbfKernel::Multiquadric(3.0), RbfKernel::InverseMultiquadric(3.0),
+               RbfKernel::ThinPlate, RbfKernel::Polyharmonic(3)].iter() {
+        let rbf: Rbf<f64> = Rbf::new(&points, &values, k).unwrap();
+        println!("f({}, {}) = {:.5} ({:?})", x, y, rbf.calc(&[x, y]), k);
+    }
+    println!("exact        {:.5}", f(x, y));
+    println!("");
+
+    println!("*** Shepard interpolation ***");
+    let sh: Shepard<f64> = Shepard::new(&points, &values, 2.0);
+    println!("f({}, {}) = {:.5}", x, y, sh.calc(&[x, y]));
+    println!("");
+
+    println!("*** Delaunay triangulation ***");
+    let d: Delaunay<f64> = Delaunay::new(&points, &values);
+    println!("{} triangles", d.triangles.len());
+    println!("f({}, {}) = {:.5} (linear)", x, y, d.calc(x, y).unwrap());
+    println!("f({}, {}) = {:.5} (natural neighbour)", x, y, d.calc_natural(x, y).unwrap());
+    println!("f(1.5, 0.5) = {:?}", d.calc(1.5, 0.5));
+}
@@ -0,0 +1,26 @@
+use numerical::simplex::*;
+
+fn main() {
+    let t = TriIntegral::<f64>::new(|x: f64, y: f64| x * x * y);
+    println!("*** f(x, y) = x^2 y ***");
+    println!("*** T: (0, 0), (1, 0), (0, 1) ***\n");
+    let p = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+
+    println!("** Dunavant (degree = 3) **");
+    println!("I = {:.6}\n", t.dunavant(p, 3));
+
+    println!("** Grundmann-Moller (s = 1) **");
+    println!("I = {:.6}\n", t.grundmann_moller(p, 1));
+
+    println!("*** P: L-shaped polygon (0, 0), (2, 0), (2, 1), (1, 1), (1, 2), (0, 2) ***");
+    let poly = vec![(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)];
+    println!("** Dunavant (degree = 3) **");
+    println!("I = {:.6}\n", t.polygon(&poly, 3));
+
+    let t = TetIntegral::<f64>::new(|x: f64, y: f64, z: f64| x * y * z);
+    println!("*** f(x, y, z) = xyz ***");
+    println!("*** T: (0, 0, 0), (1, 0, 0), (0, 1, 0), (0, 0, 1) ***\n");
+    let p = [(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)];
+    println!("** Grundmann-Moller (s = 1) **");
+    println!("I = {:.8}", t.grundmann_moller(p, 1));
+}
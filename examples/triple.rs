@@ -0,0 +1,29 @@
+use numerical::triple::*;
+
+fn main() {
+    let i = TIntegral::<f64>::new(|x: f64, y: f64, z: f64| x * y * z);
+    println!("*** f(x, y, z) = xyz ***");
+    println!("*** I = \\int\\int\\int_V f(x, y, z) dxdydz ***");
+    println!("*** V: 0 <= x <= 1, 0 <= y <= x, 0 <= z <= x + y ***\n");
+    let ab2: (fn(f64)->f64, fn(f64)->f64) = (|_x: f64| 0.0, |x: f64| x);
+    let ab3: (fn(f64, f64)->f64, fn(f64, f64)->f64) = (|_x: f64, _y: f64| 0.0, |x: f64, y: f64| x + y);
+
+    println!("** trapezoid (n = 20) **");
+    println!("n = 20: I = {:.6}\n", i.trapezoid((0.0, 1.0), ab2, ab3, 20));
+
+    println!("** Simpson (n = 10) **");
+    println!("n = 10: I = {:.6}\n", i.simpson((0.0, 1.0), ab2, ab3, 10));
+
+    println!("** Gauss-Legendre (n = 5) **");
+    println!("n = 5: I = {:.6}\n", i.gauss_legendre((0.0, 1.0), ab2, ab3, 5));
+
+    println!("** Chebyshev-Horinouchi (n = 5) **");
+    println!("n = 5: I = {:.6}\n", i.chebyshev((0.0, 1.0), ab2, ab3, 5));
+
+    println!("** Double Exponential Formula (n = 20, h = 0.1) **");
+    println!("n = 20: I = {:.6}\n", i.def((0.0, 1.0), ab2, ab3, 20, 0.1));
+
+    println!("** Adaptive Gauss-Kronrod (tol = 1e-10) **");
+    let (v, e) = i.gauss_kronrod((0.0, 1.0), ab2, ab3, (1e-10, 1e-10, 1e-10));
+    println!("I = {:.10}, error = {:.3e}", v, e);
+}
@@ -0,0 +1,315 @@
+use num_traits::float::Float;
+use num_traits::{Zero, One};
+use ndarray::*;
+use crate::fit::{sort_points, calc_piecewise};
+use crate::interpolation::Extrapolation;
+use std::cmp::Ordering;
+
+/// Spline of degree k, sum_i c[i] B_{i,k}(x) on [t[k], t[n]] (n = c.len())
+pub struct BSpline<F: Float> {
+    pub t: Vec<F>,
+    pub c: Vec<F>,
+    pub k: usize,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> BSpline<F> {
+    pub fn new(t: &Vec<F>, c: &Vec<F>, k: usize) -> Self {
+        if t.len() != c.len() + k + 1 {
+            panic!("length of t should be c.len() + k + 1!");
+        }
+        if (0..t.len()-1).any(|i| t[i] > t[i+1]) {
+            panic!("t should be non-decreasing!");
+        }
+        if t[k] >= t[c.len()] {
+            panic!("t should span a non-empty range!");
+        }
+        Self { t: t.clone(), c: c.clone(), k, ext: Extrapolation::Error }
+    }
+
+    pub fn with_extrapolation(mut self, ext: Extrapolation) -> Self {
+        self.ext = ext;
+        self
+    }
+
+    /// spline of degree k through the points, with the knots of `interpolation_knots`
+    pub fn interpolate(x: &Vec<F>, y: &Vec<F>, k: usize) -> Self {
+        let (x, y) = sort_points(x, y);
+        let n = x.len();
+        if k == 0 || n < k + 1 {
+            panic!("degree should be 1 or more and less than the number of points!");
+        }
+        let t = interpolation_knots(&x, k);
+        let spans: Vec<usize> = x.iter().map(|&xi| find_span(&t, k, xi)).collect();
+        let kl = (0..n).map(|i| i.saturating_sub(spans[i] - k)).max().unwrap();
+        let ku = (0..n).map(|i| spans[i].saturating_sub(i)).max().unwrap();
+        let mut a = vec![vec![F::zero(); kl + ku + 1]; n];
+        for i in 0..n {
+            let nb = basis_funs(&t, k, spans[i], x[i]);
+            for (r, &v) in nb.iter().enumerate() {
+                a[i][kl + spans[i] - k + r - i] = v;
+            }
+        }
+        let c = solve_banded(&a, kl, ku, &y);
+        Self { t, c, k, ext: Extrapolation::Error }
+    }
+
+    /// least-squares spline of degree k on the knots t,
+    /// every basis function should be supported by some of the points
+    pub fn fit(x: &Vec<F>, y: &Vec<F>, t: &Vec<F>, k: usize) -> Self {
+        if x.len() != y.len() {
+            panic!("x and y have different number of elements!");
+        }
+        let zero: F = Zero::zero();
+        let nc = t.len() - k - 1;
+        let mut a = vec![vec![zero; 2 * k + 1]; nc];
+        let mut b = vec![zero; nc];
+        for (&xi, &yi) in x.iter().zip(y.iter()) {
+            if !(xi >= t[k] && xi <= t[nc]) {
+                panic!("x should be in [t[k], t[n]]!");
+            }
+            let span = find_span(t, k, xi);
+            let nb = basis_funs(t, k, span, xi);
+            for r in 0..k+1 {
+                let i = span - k + r;
+                b[i] = b[i] + nb[r] * yi;
+                for s in 0..k+1 {
+                    a[i][k + s - r] = a[i][k + s - r] + nb[r] * nb[s];
+                }
+            }
+        }
+        // Schoenberg-Whitney condition: B_i(x[j_i]) > 0 for some distinct x[j_0] < x[j_1] < ...,
+        // otherwise the normal equations are singular
+        let mut xs = x.clone();
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        xs.dedup();
+        let mut j = 0;
+        for i in 0..nc {
+            let nonzero = |z: F| {
+                let span = find_span(t, k, z);
+                i <= span && i + k >= span && basis_funs(t, k, span, z)[i + k - span] > zero
+            };
+            while j < xs.len() && !nonzero(xs[j]) {
+                j += 1;
+            }
+            if j == xs.len() {
+                panic!("every basis function should be supported by a distinct point!");
+            }
+            j += 1;
+        }
+        let c = solve_banded(&a, k, k, &b);
+        Self::new(t, &c, k)
+    }
+
+    /// de Boor's algorithm, out of [t[k], t[n]] by `ext` where Polynomial continues the end pieces
+    pub fn calc(&self, z: F) -> Result<F, &'static str> {
+        let (lo, hi) = (self.t[self.k], self.t[self.c.len()]);
+        if z >= lo && z <= hi {
+            return Ok(self.eval(z, 0));
+        }
+        calc_piecewise(&vec![lo, hi], self.ext, z, 0, |_, dz, d| self.eval(lo + dz, d))
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Result<Array<F, D>, &'static str> {
+        let mut ret = Vec::with_capacity(z.len());
+        for &zi in z.iter() {
+            ret.push(self.calc(zi)?);
+        }
+        Ok(Array::from_shape_vec(z.raw_dim(), ret).unwrap())
+    }
+
+    /// derivative of order d at z by de Boor's algorithm (d = 0) or the basis derivatives
+    fn eval(&self, z: F, d: usize) -> F {
+        let one: F = One::one();
+        let k = self.k;
+        let t = &self.t;
+        let span = find_span(t, k, z);
+        if d > 0 {
+            let ders = basis_ders(t, k, span, z, d);
+            return (0..k+1).fold(F::zero(), |m, r| m + self.c[span - k + r] * ders[d][r]);
+        }
+        let mut p: Vec<F> = self.c[span-k..span+1].to_vec();
+        for r in 1..k+1 {
+            for j in (r..k+1).rev() {
+                let i = j + span - k;
+                let alpha = (z - t[i]) / (t[i+k+1-r] - t[i]);
+                p[j] = (one - alpha) * p[j-1] + alpha * p[j];
+            }
+        }
+        p[k]
+    }
+
+    /// spline of degree k - 1 on t[1..n+k]
+    pub fn derivative(&self) -> Self {
+        let k = self.k;
+        let n = self.c.len();
+        if k == 0 {
+            return Self { t: self.t.clone(), c: vec![Zero::zero(); n], k, ext: self.ext };
+        }
+        let kf: F = F::from(k).unwrap();
+        let c = (0..n-1).map(|i| {
+            let h = self.t[i+k+1] - self.t[i+1];
+            if h > Zero::zero() { kf * (self.c[i+1] - self.c[i]) / h } else { Zero::zero() }
+        }).collect();
+        Self { t: self.t[1..n+k].to_vec(), c, k: k - 1, ext: self.ext }
+    }
+}
+
+/// knot vector of degree k with k + 1 fold end knots and the sorted breaks x
+pub fn clamped_knots<F: Float>(x: &Vec<F>, k: usize) -> Vec<F> {
+    let n = x.len();
+    let mut t = vec![x[0]; k];
+    t.extend(x.iter().cloned());
+    t.extend(vec![x[n-1]; k]);
+    t
+}
+
+/// clamped knot vector of degree k with m uniform intervals on [a, b]
+pub fn uniform_knots<F: Float>(a: F, b: F, m: usize, k: usize) -> Vec<F> {
+    let h = (b - a) / F::from(m).unwrap();
+    let x: Vec<F> = (0..m+1).map(|i| if i == m { b } else { a + F::from(i).unwrap() * h }).collect();
+    clamped_knots(&x, k)
+}
+
+/// knots for interpolation at the sorted x: the interior knots average k
+/// consecutive points (de Boor), so the Schoenberg-Whitney condition holds
+pub fn interpolation_knots<F: Float>(x: &Vec<F>, k: usize) -> Vec<F> {
+    let n = x.len();
+    let kf: F = F::from(k).unwrap();
+    let mut t = vec![x[0]; k + 1];
+    for j in 1..n-k {
+        t.push(x[j..j+k].iter().fold(F::zero(), |m, &v| m + v) / kf);
+    }
+    t.extend(vec![x[n-1]; k + 1]);
+    t
+}
+
+/// index i (k <= i < n) with t[i] <= z < t[i+1], the last one at the right end
+pub fn find_span<F: Float>(t: &Vec<F>, k: usize, z: F) -> usize {
+    let n = t.len() - k - 1;
+    let i = match t.binary_search_by(|ti| if *ti > z { Ordering::Greater } else { Ordering::Less }) {
+        Ok(i) | Err(i) => i,
+    };
+    if i == 0 { k } else { (i - 1).max(k).min(n - 1) }
+}
+
+/// Cox-de Boor recursion for the k + 1 nonzero basis functions B_{span-k..span}(z)
+pub fn basis_funs<F: Float>(t: &Vec<F>, k: usize, span: usize, z: F) -> Vec<F> {
+    let zero: F = Zero::zero();
+    let mut nb = vec![zero; k + 1];
+    let mut left = vec![zero; k + 1];
+    let mut right = vec![zero; k + 1];
+    nb[0] = One::one();
+    for j in 1..k+1 {
+        left[j] = z - t[span+1-j];
+        right[j] = t[span+j] - z;
+        let mut saved = zero;
+        for r in 0..j {
+            let temp = nb[r] / (right[r+1] + left[j-r]);
+            nb[r] = saved + right[r+1] * temp;
+            saved = left[j-r] * temp;
+        }
+        nb[j] = saved;
+    }
+    nb
+}
+
+/// derivatives of order 0..=nd of the nonzero basis functions, ders[order][r] for B_{span-k+r}
+pub fn basis_ders<F: Float>(t: &Vec<F>, k: usize, span: usize, z: F, nd: usize) -> Vec<Vec<F>> {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let p = k as isize;
+    let mut ndu = vec![vec![zero; k + 1]; k + 1];
+    let mut left = vec![zero; k + 1];
+    let mut right = vec![zero; k + 1];
+    ndu[0][0] = one;
+    for j in 1..k+1 {
+        left[j] = z - t[span+1-j];
+        right[j] = t[span+j] - z;
+        let mut saved = zero;
+        for r in 0..j {
+            ndu[j][r] = right[r+1] + left[j-r];
+            let temp = ndu[r][j-1] / ndu[j][r];
+            ndu[r][j] = saved + right[r+1] * temp;
+            saved = left[j-r] * temp;
+        }
+        ndu[j][j] = saved;
+    }
+    let mut ders = vec![vec![zero; k + 1]; nd + 1];
+    for j in 0..k+1 {
+        ders[0][j] = ndu[j][k];
+    }
+    let nk = nd.min(k) as isize;
+    let mut a = vec![vec![zero; k + 1]; 2];
+    for r in 0..p+1 {
+        let (mut s1, mut s2) = (0, 1);
+        a[0][0] = one;
+        for kk in 1..nk+1 {
+            let mut d = zero;
+            let rk = r - kk;
+            let pk = p - kk;
+            if r >= kk {
+                a[s2][0] = a[s1][0] / ndu[(pk+1) as usize][rk as usize];
+                d = a[s2][0] * ndu[rk as usize][pk as usize];
+            }
+            let j1 = if rk >= -1 { 1 } else { -rk };
+            let j2 = if r - 1 <= pk { kk - 1 } else { p - r };
+            for j in j1..j2+1 {
+                let (j, rkj) = (j as usize, (rk + j) as usize);
+                a[s2][j] = (a[s1][j] - a[s1][j-1]) / ndu[(pk+1) as usize][rkj];
+                d = d + a[s2][j] * ndu[rkj][pk as usize];
+            }
+            if r <= pk {
+                a[s2][kk as usize] = zero - a[s1][(kk-1) as usize] / ndu[(pk+1) as usize][r as usize];
+                d = d + a[s2][kk as usize] * ndu[r as usize][pk as usize];
+            }
+            ders[kk as usize][r as usize] = d;
+            std::mem::swap(&mut s1, &mut s2);
+        }
+    }
+    let mut m: F = F::from(k).unwrap();
+    for kk in 1..(nk as usize)+1 {
+        for j in 0..k+1 {
+            ders[kk][j] = ders[kk][j] * m;
+        }
+        m = m * F::from(k - kk).unwrap();
+    }
+    ders
+}
+
+/// values of all the t.len() - k - 1 basis functions of degree k at z in [t[k], t[n]]
+pub fn basis<F: Float>(t: &Vec<F>, k: usize, z: F) -> Vec<F> {
+    let n = t.len() - k - 1;
+    let span = find_span(t, k, z);
+    let mut ret = vec![F::zero(); n];
+    for (r, v) in basis_funs(t, k, span, z).into_iter().enumerate() {
+        ret[span - k + r] = v;
+    }
+    ret
+}
+
+/// Gaussian elimination without pivoting for the banded matrix a[i][kl + j - i] = A(i, j)
+/// (kl sub-diagonals, ku super-diagonals), e.g. totally positive or positive definite
+pub fn solve_banded<F: Float>(a: &Vec<Vec<F>>, kl: usize, ku: usize, b: &Vec<F>) -> Vec<F> {
+    let n = b.len();
+    let mut a = a.clone();
+    let mut x = b.clone();
+    for p in 0..n {
+        let piv = a[p][kl];
+        for i in p+1..n.min(p + kl + 1) {
+            let l = a[i][kl + p - i] / piv;
+            if l == F::zero() {
+                continue;
+            }
+            for j in p..n.min(p + ku + 1) {
+                a[i][kl + j - i] = a[i][kl + j - i] - l * a[p][kl + j - p];
+            }
+            x[i] = x[i] - l * x[p];
+        }
+    }
+    for p in (0..n).rev() {
+        let s = (p+1..n.min(p + ku + 1)).fold(x[p], |m, j| m - a[p][kl + j - p] * x[j]);
+        x[p] = s / a[p][kl];
+    }
+    x
+}
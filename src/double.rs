@@ -172,6 +172,17 @@ impl<F:Float + FloatConst> DIntegral<F> {
         }
         s1 * h * (b1 - a1) / two
     }
+
+    /// nested adaptive Gauss-Kronrod rule with tolerances (x, y),
+    /// returns (integral, error estimate including the propagated inner errors)
+    pub fn gauss_kronrod(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), tol: (F, F)) -> (F, F) {
+        let (a1, b1) = ab1;
+        let inner = |x: F| {
+            let (a2, b2) = ((ab2.0)(x), (ab2.1)(x));
+            adaptive_gk(|y: F| ((self.rhs)(x, y), Zero::zero()), a2, b2, tol.1)
+        };
+        adaptive_gk(inner, a1, b1, tol.0)
+    }
 }
 
 fn _trapezoid<F:Float + FloatConst>(rhs: fn(F, F) -> F, x: F, a: F, b: F, n: usize) -> F {
@@ -1,115 +1,1368 @@
-use num_traits::float::Float;
-use num_traits::{Zero, One};
+use num_traits::float::{Float, FloatConst};
+use num_traits::{Zero, One, NumCast};
 use ndarray::*;
 use ndarray_linalg::*;
 use ndarray_linalg::lapack::Lapack;
+use crate::interpolation::Extrapolation;
+use crate::stats::student_t_quantile;
 
-pub struct Spline3d<F: Float + Lapack> {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplineBoundary<F: Float> {
+    /// first derivatives at both ends
+    Clamped(F, F),
+    /// zero second derivatives at both ends
+    Natural,
+    /// continuous third derivative at the second and the second last knots
+    NotAKnot,
+    /// y[0] == y[n-1], first and second derivatives match at both ends
+    Periodic,
+    /// second derivatives at both ends
+    SecondDerivative(F, F),
+}
+
+pub struct Spline3d<F: Float> {
     pub x: Vec<F>,
     pub y: Vec<F>,
     pub abc: Vec<(F, F, F)>,
+    pub ext: Extrapolation,
 }
 
-impl<F: Float + Lapack> Spline3d<F> {
+impl<F: Float> Spline3d<F> {
+    /// spline with the first derivatives c0 and cn at both ends
     pub fn new(x: &Vec<F>, y: &Vec<F>, c0: F, cn: F) -> Self {
+        Self::with_boundary(x, y, SplineBoundary::Clamped(c0, cn))
+    }
+
+    pub fn with_boundary(x: &Vec<F>, y: &Vec<F>, bc: SplineBoundary<F>) -> Self {
+        let (x, y) = sort_points(x, y);
+        let c = spline_slopes(&x, &y, bc);
+        let abc = hermite_coeff(&x, &y, &c);
+        Self { x, y, abc, ext: Extrapolation::Error }
+    }
+
+    pub fn with_extrapolation(mut self, ext: Extrapolation) -> Self {
+        self.ext = ext;
+        self
+    }
+
+    pub fn calc(&self, z: F) -> Result<F, &str> {
+        calc_piecewise(&self.x, self.ext, z, 0, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Result<Array<F, D>, &'static str> {
+        calc_piecewise_array(&self.x, self.ext, z, 0, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    /// calc on non-decreasing z without repeated binary search
+    pub fn calc_sorted(&self, z: &Array1<F>) -> Result<Array1<F>, &'static str> {
+        calc_piecewise_sorted(&self.x, self.ext, z, 0, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    pub fn derivative(&self, z: F) -> Result<F, &str> {
+        calc_piecewise(&self.x, self.ext, z, 1, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    pub fn second_derivative(&self, z: F) -> Result<F, &str> {
+        calc_piecewise(&self.x, self.ext, z, 2, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    /// definite integral over [a, b], extended outside the knots by `ext`
+    pub fn integral(&self, a: F, b: F) -> Result<F, &'static str> {
+        self.to_poly().integral(a, b)
+    }
+
+    /// antiderivative vanishing at the first knot
+    pub fn antiderivative(&self) -> PiecewisePoly<F> {
+        self.to_poly().antiderivative()
+    }
+
+    /// all x with S(x) = y, in increasing order
+    pub fn solve(&self, y: F) -> Vec<F> {
+        self.to_poly().solve(y)
+    }
+
+    pub fn to_poly(&self) -> PiecewisePoly<F> {
+        let coeff = self.abc.iter().zip(self.y.iter()).map(|(&(a, b, c), &yi)| vec![yi, c, b, a]).collect();
+        PiecewisePoly { x: self.x.clone(), coeff, ext: self.ext }
+    }
+}
+
+/// Piecewise polynomial, sum_k coeff[i][k] (x - x[i])^k on [x[i], x[i+1]]
+pub struct PiecewisePoly<F: Float> {
+    pub x: Vec<F>,
+    pub coeff: Vec<Vec<F>>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> PiecewisePoly<F> {
+    pub fn with_extrapolation(mut self, ext: Extrapolation) -> Self {
+        self.ext = ext;
+        self
+    }
+
+    pub fn calc(&self, z: F) -> Result<F, &'static str> {
+        calc_piecewise(&self.x, self.ext, z, 0, |i, t, d| poly_at(&self.coeff[i], t, d))
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Result<Array<F, D>, &'static str> {
+        calc_piecewise_array(&self.x, self.ext, z, 0, |i, t, d| poly_at(&self.coeff[i], t, d))
+    }
+
+    /// calc on non-decreasing z without repeated binary search
+    pub fn calc_sorted(&self, z: &Array1<F>) -> Result<Array1<F>, &'static str> {
+        calc_piecewise_sorted(&self.x, self.ext, z, 0, |i, t, d| poly_at(&self.coeff[i], t, d))
+    }
+
+    pub fn derivative(&self) -> Self {
+        let coeff = self.coeff.iter().map(|c| {
+            if c.len() < 2 {
+                vec![F::zero()]
+            } else {
+                (1..c.len()).map(|k| c[k] * F::from(k).unwrap()).collect()
+            }
+        }).collect();
+        Self { x: self.x.clone(), coeff, ext: self.ext }
+    }
+
+    /// continuous antiderivative vanishing at x[0]
+    pub fn antiderivative(&self) -> Self {
+        let mut s: F = Zero::zero();
+        let coeff = self.coeff.iter().enumerate().map(|(i, c)| {
+            let mut p: Vec<F> = vec![s];
+            p.extend(c.iter().enumerate().map(|(k, &ck)| ck / F::from(k + 1).unwrap()));
+            s = horner(&p, self.x[i+1] - self.x[i]);
+            p
+        }).collect();
+        Self { x: self.x.clone(), coeff, ext: self.ext }
+    }
+
+    /// definite integral over [a, b], extended outside the breakpoints by `ext`
+    pub fn integral(&self, a: F, b: F) -> Result<F, &'static str> {
+        let p = self.antiderivative();
+        Ok(self.primitive(&p, b)? - self.primitive(&p, a)?)
+    }
+
+    /// antiderivative p at z, continued as the integral of the extrapolated polynomial
+    fn primitive(&self, p: &Self, z: F) -> Result<F, &'static str> {
+        let two: F = F::from(2).unwrap();
+        let n = self.x.len();
+        let (lo, hi) = (self.x[0], self.x[n-1]);
+        let at = |t: F| calc_piecewise(&p.x, Extrapolation::Error, t, 0, |i, t, d| poly_at(&p.coeff[i], t, d));
+        if z.is_nan() {
+            return Err("out of range!");
+        }
+        if z >= lo && z <= hi {
+            return at(z);
+        }
+        let end = if z < lo { lo } else { hi };
+        let dz = z - end;
+        let f = |d: usize| calc_piecewise(&self.x, Extrapolation::Error, end, d, |i, t, d| poly_at(&self.coeff[i], t, d));
+        match self.ext {
+            Extrapolation::Error => Err("out of range!"),
+            Extrapolation::Clamp => Ok(at(end)? + f(0)? * dz),
+            Extrapolation::Linear => Ok(at(end)? + (f(0)? + f(1)? * dz / two) * dz),
+            Extrapolation::Polynomial => calc_piecewise(&p.x, Extrapolation::Polynomial, z, 0, |i, t, d| poly_at(&p.coeff[i], t, d)),
+            Extrapolation::Periodic => {
+                if z.is_infinite() {
+                    return Err("out of range!");
+                }
+                let period = hi - lo;
+                let k = ((z - lo) / period).floor();
+                let w = (z - k * period).max(lo).min(hi);
+                Ok(at(w)? + k * (at(hi)? - at(lo)?))
+            },
+        }
+    }
+
+    /// all x with P(x) = y, in increasing order (segments equal to y everywhere are skipped)
+    pub fn solve(&self, y: F) -> Vec<F> {
+        let mut roots: Vec<F> = Vec::new();
+        for (i, c) in self.coeff.iter().enumerate() {
+            let mut p = c.clone();
+            p[0] = p[0] - y;
+            for t in poly_roots(&p, Zero::zero(), self.x[i+1] - self.x[i]).into_iter() {
+                let r = self.x[i] + t;
+                if roots.last().map_or(true, |&l| r > l) {
+                    roots.push(r);
+                }
+            }
+        }
+        roots
+    }
+}
+
+/// value (order 0) or derivative at z of the piecewise function seg(i, z - x[i], order)
+/// on sorted x, extended outside [x[0], x[n-1]] by ext
+pub fn calc_piecewise<F: Float, G: Fn(usize, F, usize) -> F>(x: &Vec<F>, ext: Extrapolation, z: F, order: usize, seg: G) -> Result<F, &'static str> {
+    piecewise_at(x, ext, z, order, &seg, &mut 0, false)
+}
+
+/// calc_piecewise at every element of z, trying the segment of the previous element first
+pub fn calc_piecewise_array<F: Float, D: Dimension, G: Fn(usize, F, usize) -> F>(x: &Vec<F>, ext: Extrapolation, z: &Array<F, D>, order: usize, seg: G) -> Result<Array<F, D>, &'static str> {
+    let mut hint = 0;
+    let mut ret = Vec::with_capacity(z.len());
+    for &zi in z.iter() {
+        ret.push(piecewise_at(x, ext, zi, order, &seg, &mut hint, false)?);
+    }
+    Ok(Array::from_shape_vec(z.raw_dim(), ret).unwrap())
+}
+
+/// calc_piecewise at every element of non-decreasing z, walking the segments forward
+pub fn calc_piecewise_sorted<F: Float, G: Fn(usize, F, usize) -> F>(x: &Vec<F>, ext: Extrapolation, z: &Array1<F>, order: usize, seg: G) -> Result<Array1<F>, &'static str> {
+    if z.iter().zip(z.iter().skip(1)).any(|(&a, &b)| !(a <= b)) {
+        return Err("z should be sorted!");
+    }
+    let mut hint = 0;
+    let mut ret = Vec::with_capacity(z.len());
+    for &zi in z.iter() {
+        ret.push(piecewise_at(x, ext, zi, order, &seg, &mut hint, true)?);
+    }
+    Ok(Array1::from(ret))
+}
+
+fn piecewise_at<F: Float, G: Fn(usize, F, usize) -> F>(x: &Vec<F>, ext: Extrapolation, z: F, order: usize, seg: &G, hint: &mut usize, walk: bool) -> Result<F, &'static str> {
+    let zero: F = Zero::zero();
+    let n = x.len();
+    let (lo, hi) = (x[0], x[n-1]);
+    if z.is_nan() {
+        return Err("out of range!");
+    }
+    if z >= lo && z <= hi {
+        let mut i = *hint;
+        if walk {
+            while i + 2 < n && z > x[i+1] {
+                i += 1;
+            }
+        } else {
+            i = locate(x, z, i);
+        }
+        *hint = i;
+        return Ok(seg(i, z - x[i], order));
+    }
+    let (i, end) = if z < lo { (0, lo) } else { (n-2, hi) };
+    match ext {
+        Extrapolation::Error => Err("out of range!"),
+        Extrapolation::Clamp => Ok(if order == 0 { seg(i, end - x[i], 0) } else { zero }),
+        Extrapolation::Linear => match order {
+            0 => Ok(seg(i, end - x[i], 0) + seg(i, end - x[i], 1) * (z - end)),
+            1 => Ok(seg(i, end - x[i], 1)),
+            _ => Ok(zero),
+        },
+        Extrapolation::Polynomial => Ok(seg(i, z - x[i], order)),
+        Extrapolation::Periodic => {
+            if z.is_infinite() {
+                return Err("out of range!");
+            }
+            let p = hi - lo;
+            let mut w = (z - lo) % p;
+            if w < zero {
+                w = w + p;
+            }
+            let w = (lo + w).min(hi);
+            let i = locate(x, w, *hint);
+            Ok(seg(i, w - x[i], order))
+        },
+    }
+}
+
+/// segment of z in [x[0], x[n-1]], checking the segment hint and its successor
+/// before falling back to binary search
+fn locate<F: Float>(x: &Vec<F>, z: F, hint: usize) -> usize {
+    let n = x.len();
+    let i = hint.min(n - 2);
+    if z >= x[i] && z <= x[i+1] {
+        i
+    } else if i + 2 < n && z > x[i+1] && z <= x[i+2] {
+        i + 1
+    } else {
+        match x.binary_search_by(|xi| xi.partial_cmp(&z).unwrap()) {
+            Ok(index) => index.min(n-2),
+            Err(index) => index - 1,
+        }
+    }
+}
+
+/// index i of the segment [x[i], x[i+1]] containing z
+pub fn segment<F: Float>(x: &Vec<F>, z: F) -> Result<usize, &'static str> {
+    if !(z >= x[0] && z <= x[x.len()-1]) {
+        return Err("out of range!");
+    }
+    Ok(locate(x, z, 0))
+}
+
+/// order-th derivative of y0 + c t + b t^2 + a t^3
+fn cubic_at<F: Float>(y0: F, abc: (F, F, F), t: F, order: usize) -> F {
+    let (a, b, c) = abc;
+    let two: F = F::from(2).unwrap();
+    let three: F = F::from(3).unwrap();
+    let six: F = F::from(6).unwrap();
+    match order {
+        0 => y0 + t * (c + t * (b + t * a)),
+        1 => c + t * (two * b + three * a * t),
+        2 => two * b + six * a * t,
+        3 => six * a,
+        _ => Zero::zero(),
+    }
+}
+
+/// order-th derivative of sum_k c[k] t^k
+fn poly_at<F: Float>(c: &Vec<F>, t: F, order: usize) -> F {
+    let dc: Vec<F> = (order..c.len()).map(|k| {
+        (k-order+1..k+1).fold(c[k], |m, j| m * F::from(j).unwrap())
+    }).collect();
+    horner(&dc, t)
+}
+
+/// sum_k c[k] t^k
+pub fn horner<F: Float>(c: &Vec<F>, t: F) -> F {
+    c.iter().rev().fold(F::zero(), |m, &ck| m * t + ck)
+}
+
+/// real roots in [lo, hi] of sum_k c[k] t^k, found by bisection on the monotone
+/// pieces between the roots of the derivative; a root of the derivative is also a root
+/// (of even multiplicity) if the value vanishes to the rounding of the terms
+pub fn poly_roots<F: Float>(c: &Vec<F>, lo: F, hi: F) -> Vec<F> {
+    let zero: F = Zero::zero();
+    let mut c = c.clone();
+    while c.len() > 1 && *c.last().unwrap() == zero {
+        c.pop();
+    }
+    if c.len() < 2 {
+        return Vec::new();
+    }
+    let dc: Vec<F> = (1..c.len()).map(|k| c[k] * F::from(k).unwrap()).collect();
+    let mut knots = vec![lo];
+    knots.extend(poly_roots(&dc, lo, hi).into_iter().filter(|&t| t > lo && t < hi));
+    knots.push(hi);
+    let nk = knots.len();
+    let ac: Vec<F> = c.iter().map(|v| v.abs()).collect();
+    let tol = F::from(64).unwrap() * F::epsilon();
+    let touch: Vec<bool> = knots.iter().enumerate().map(|(i, &t)| {
+        let f = horner(&c, t);
+        f == zero || (i > 0 && i < nk - 1 && f.abs() <= tol * horner(&ac, t.abs()))
+    }).collect();
+    let mut roots: Vec<F> = Vec::new();
+    for (i, w) in knots.windows(2).enumerate() {
+        let (mut a, mut b) = (w[0], w[1]);
+        let (fa, fb) = (horner(&c, a), horner(&c, b));
+        let root = if touch[i] {
+            Some(a)
+        } else if touch[i+1] {
+            Some(b)
+        } else if (fa < zero) != (fb < zero) {
+            for _ in 0..200 {
+                let mid = (a + b) / F::from(2).unwrap();
+                if mid <= a || mid >= b {
+                    break;
+                }
+                if (horner(&c, mid) < zero) == (fa < zero) {
+                    a = mid;
+                } else {
+                    b = mid;
+                }
+            }
+            Some((a + b) / F::from(2).unwrap())
+        } else {
+            None
+        };
+        if let Some(r) = root {
+            if roots.last().map_or(true, |&l| r > l) {
+                roots.push(r);
+            }
+        }
+    }
+    roots
+}
+
+/// Piecewise cubic Hermite interpolation on values and first derivatives
+pub struct CubicHermite<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub dy: Vec<F>,
+    pub abc: Vec<(F, F, F)>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> CubicHermite<F> {
+    pub fn new(x: &Vec<F>, y: &Vec<F>, dy: &Vec<F>) -> Self {
+        if x.len() != y.len() || x.len() != dy.len() {
+            panic!("x, y and dy have different number of elements!");
+        }
+        let (x, ydy) = sort_points(x, &(0..y.len()).map(|i| (y[i], dy[i])).collect());
+        let y: Vec<F> = ydy.iter().map(|p| p.0).collect();
+        let dy: Vec<F> = ydy.iter().map(|p| p.1).collect();
+        let abc = hermite_coeff(&x, &y, &dy);
+        Self { x, y, dy, abc, ext: Extrapolation::Error }
+    }
+
+    pub fn with_extrapolation(mut self, ext: Extrapolation) -> Self {
+        self.ext = ext;
+        self
+    }
+
+    /// monotone piecewise cubic (Fritsch-Carlson) with the slopes of Fritsch-Butland
+    pub fn pchip(x: &Vec<F>, y: &Vec<F>) -> Self {
+        let zero: F = Zero::zero();
+        let two: F = F::from(2).unwrap();
+        let three: F = F::from(3).unwrap();
+        let (x, y) = sort_points(x, y);
+        let (h, u) = secants(&x, &y);
+        let n = x.len();
+        if n == 2 {
+            return Self::sorted(x, y, vec![u[0], u[0]]);
+        }
+        let mut dy = vec![zero; n];
+        for i in 1..n-1 {
+            if u[i-1] * u[i] > zero {
+                let w1 = two * h[i] + h[i-1];
+                let w2 = h[i] + two * h[i-1];
+                dy[i] = (w1 + w2) / (w1 / u[i-1] + w2 / u[i]);
+            }
+        }
+        let end = |h0: F, h1: F, u0: F, u1: F| {
+            let d = ((two * h0 + h1) * u0 - h0 * u1) / (h0 + h1);
+            if sign(d) != sign(u0) {
+                zero
+            } else if sign(u0) != sign(u1) && d.abs() > three * u0.abs() {
+                three * u0
+            } else {
+                d
+            }
+        };
+        dy[0] = end(h[0], h[1], u[0], u[1]);
+        dy[n-1] = end(h[n-2], h[n-3], u[n-2], u[n-3]);
+        Self::sorted(x, y, dy)
+    }
+
+    /// Akima's local slopes weighted by the differences of neighbouring secants
+    pub fn akima(x: &Vec<F>, y: &Vec<F>) -> Self {
+        let two: F = F::from(2).unwrap();
+        let (x, y) = sort_points(x, y);
+        let (_, u) = secants(&x, &y);
+        let n = x.len();
+        if n == 2 {
+            return Self::sorted(x, y, vec![u[0], u[0]]);
+        }
+        // m[i+2] = u[i], two secants extrapolated linearly on each side
+        let mut m = vec![two * (two * u[0] - u[1]) - u[0], two * u[0] - u[1]];
+        m.extend(u.iter().cloned());
+        let (a, b) = (u[n-2], u[n-3]);
+        m.push(two * a - b);
+        m.push(two * (two * a - b) - a);
+        let dy = (0..n).map(|i| {
+            let w1 = (m[i+3] - m[i+2]).abs();
+            let w2 = (m[i+1] - m[i]).abs();
+            if w1 + w2 == F::zero() {
+                (m[i+1] + m[i+2]) / two
+            } else {
+                (w1 * m[i+1] + w2 * m[i+2]) / (w1 + w2)
+            }
+        }).collect();
+        Self::sorted(x, y, dy)
+    }
+
+    /// Steffen's monotone interpolation, no overshoot within any segment
+    pub fn steffen(x: &Vec<F>, y: &Vec<F>) -> Self {
         let zero: F = Zero::zero();
         let one: F = One::one();
         let two: F = one + one;
-        let three: F = two + one;
+        let (x, y) = sort_points(x, y);
+        let (h, u) = secants(&x, &y);
         let n = x.len();
-        if n != y.len() {
-            panic!("x and y have different number of elements!");
-        }
-        let mut xy: Vec<(F, F)> = x.iter().zip(y.iter()).map(|(&xi, &yi)| (xi, yi)).collect();
-        xy.sort_by(|a, b| (a.0).partial_cmp(&b.0).unwrap());
-        let h: Vec<F> = (0..n-1).map(|i| xy[i+1].0 - xy[i].0).collect();
-        let u: Vec<F> = (0..n-1).map(|i| (xy[i+1].1 - xy[i].1) / h[i]).collect();
-        let mut a: Array2<F> = Array2::zeros((n-2, n-2));
-        let mut b: Vec<F> = vec![zero; n-2];
-        for i in 0..n-2 {
-            if i == 0 {
-                b[i] = b[i] - h[i+1] * c0;
-                a[[i, i+1]] = h[i];
-            } else if i == n-3 {
-                b[i] = b[i] - h[i] * cn;
-                a[[i, i-1]] = h[i+1];
+        if n == 2 {
+            return Self::sorted(x, y, vec![u[0], u[0]]);
+        }
+        let mut dy = vec![zero; n];
+        for i in 1..n-1 {
+            let p = (u[i-1] * h[i] + u[i] * h[i-1]) / (h[i-1] + h[i]);
+            let m = u[i-1].abs().min(u[i].abs()).min(p.abs() / two);
+            dy[i] = (sign(u[i-1]) + sign(u[i])) * m;
+        }
+        let end = |h0: F, h1: F, u0: F, u1: F| {
+            let p = u0 * (one + h0 / (h0 + h1)) - u1 * h0 / (h0 + h1);
+            if p * u0 <= zero {
+                zero
+            } else if p.abs() > two * u0.abs() {
+                two * u0
             } else {
-                a[[i, i-1]] = h[i+1];
-                a[[i, i+1]] = h[i];
+                p
             }
-            a[[i, i]] = two * (h[i] + h[i+1]);
-            b[i] = b[i] + three * (h[i+1] * u[i] + h[i] * u[i+1]);
+        };
+        dy[0] = end(h[0], h[1], u[0], u[1]);
+        dy[n-1] = end(h[n-2], h[n-3], u[n-2], u[n-3]);
+        Self::sorted(x, y, dy)
+    }
+
+    fn sorted(x: Vec<F>, y: Vec<F>, dy: Vec<F>) -> Self {
+        let abc = hermite_coeff(&x, &y, &dy);
+        Self { x, y, dy, abc, ext: Extrapolation::Error }
+    }
+
+    pub fn calc(&self, z: F) -> Result<F, &str> {
+        calc_piecewise(&self.x, self.ext, z, 0, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Result<Array<F, D>, &'static str> {
+        calc_piecewise_array(&self.x, self.ext, z, 0, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    /// calc on non-decreasing z without repeated binary search
+    pub fn calc_sorted(&self, z: &Array1<F>) -> Result<Array1<F>, &'static str> {
+        calc_piecewise_sorted(&self.x, self.ext, z, 0, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+
+    pub fn derivative(&self, z: F) -> Result<F, &str> {
+        calc_piecewise(&self.x, self.ext, z, 1, |i, t, d| cubic_at(self.y[i], self.abc[i], t, d))
+    }
+}
+
+/// Cubic smoothing spline minimizing sum w_i (y_i - f(x_i))^2 + lambda int f''(x)^2 dx
+/// (Reinsch algorithm), points with the same x are merged into their weighted mean
+pub struct SmoothingSpline<F: Float> {
+    pub lambda: F,
+    /// trace of the influence matrix (effective degrees of freedom)
+    pub edf: F,
+    /// generalized cross-validation score
+    pub gcv: F,
+    pub spline: Spline3d<F>,
+}
+
+impl<F: Float> SmoothingSpline<F> {
+    pub fn new(x: &Vec<F>, y: &Vec<F>, lambda: F) -> Self {
+        Self::with_weights(x, y, &vec![One::one(); x.len()], lambda)
+    }
+
+    pub fn with_weights(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, lambda: F) -> Self {
+        if !(lambda >= Zero::zero()) {
+            panic!("lambda should be non-negative!");
         }
+        let (x, y, w) = merge_points(x, y, w);
+        Self::sorted(&x, &y, &w, lambda)
+    }
 
-        let b = Array1::from(b);
-        let mut c = a.solve(&b).unwrap().to_vec();
-        c.insert(0, c0);
-        c.push(cn);
-        let b: Vec<F> = (0..n-1).map(|i| (three * u[i] - two * c[i] - c[i+1]) / h[i]).collect();
-        let a: Vec<F> = (0..n-1).map(|i| (u[i] - h[i] * b[i] - c[i]) / (h[i] * h[i])).collect();
-        let abc: Vec<(F, F, F)> = (0..n-1).map(|i| (a[i], b[i], c[i])).collect();
+    /// lambda minimizing the generalized cross-validation score, found by a scan
+    /// over 24 decades of lambda followed by golden section search on log(lambda)
+    pub fn gcv(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>) -> Self {
+        let (x, y, w) = merge_points(x, y, w);
+        let ten: F = F::from(10).unwrap();
+        let (r, q) = scales(&x, &w);
+        let p0 = (r / q).log10();
+        let score = |p: F| Self::sorted(&x, &y, &w, ten.powf(p)).gcv;
+        let ps: Vec<F> = (0..49).map(|i| p0 + F::from(i as f64 / 2.0 - 12.0).unwrap()).collect();
+        let p = scan_min(&ps, score);
+        Self::sorted(&x, &y, &w, ten.powf(p))
+    }
 
-        Self { x: x.clone(), y: y.clone(), abc }
+    fn sorted(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, lambda: F) -> Self {
+        let zero: F = Zero::zero();
+        let one: F = One::one();
+        let two: F = one + one;
+        let three: F = two + one;
+        let six: F = three + three;
+        let n = x.len();
+        let m = n - 2;
+        let h: Vec<F> = (0..n-1).map(|i| x[i+1] - x[i]).collect();
+        let v: Vec<F> = w.iter().map(|&wi| one / wi).collect();
+        // Q[i][j], j-th column for the interior knot j + 1
+        let q = |i: usize, j: usize| {
+            if i == j {
+                one / h[j]
+            } else if i == j + 1 {
+                zero - one / h[j] - one / h[j+1]
+            } else if i == j + 2 {
+                one / h[j+1]
+            } else {
+                zero
+            }
+        };
+        // bands of R + lambda Q^T W^-1 Q
+        let b0: Vec<F> = (0..m).map(|j| {
+            (h[j] + h[j+1]) / three + lambda * (j..j+3).fold(zero, |s, i| s + v[i] * q(i, j) * q(i, j))
+        }).collect();
+        let b1: Vec<F> = (0..m).map(|j| {
+            if j + 1 < m {
+                h[j+1] / six + lambda * (j+1..j+3).fold(zero, |s, i| s + v[i] * q(i, j) * q(i, j+1))
+            } else {
+                zero
+            }
+        }).collect();
+        let b2: Vec<F> = (0..m).map(|j| {
+            if j + 2 < m { lambda * v[j+2] * q(j+2, j) * q(j+2, j+2) } else { zero }
+        }).collect();
+        // L D L^T
+        let mut d = vec![zero; m];
+        let mut l1 = vec![zero; m];
+        let mut l2 = vec![zero; m];
+        for i in 0..m {
+            let mut di = b0[i];
+            let mut e = b1[i];
+            if i >= 1 {
+                di = di - l1[i-1] * l1[i-1] * d[i-1];
+                e = e - l2[i-1] * l1[i-1] * d[i-1];
+            }
+            if i >= 2 {
+                di = di - l2[i-2] * l2[i-2] * d[i-2];
+            }
+            d[i] = di;
+            l1[i] = e / di;
+            l2[i] = b2[i] / di;
+        }
+        let mut gamma: Vec<F> = (0..m).map(|j| (j..j+3).fold(zero, |s, i| s + q(i, j) * y[i])).collect();
+        for i in 0..m {
+            if i >= 1 {
+                gamma[i] = gamma[i] - l1[i-1] * gamma[i-1];
+            }
+            if i >= 2 {
+                gamma[i] = gamma[i] - l2[i-2] * gamma[i-2];
+            }
+        }
+        for i in (0..m).rev() {
+            gamma[i] = gamma[i] / d[i];
+            if i + 1 < m {
+                gamma[i] = gamma[i] - l1[i] * gamma[i+1];
+            }
+            if i + 2 < m {
+                gamma[i] = gamma[i] - l2[i] * gamma[i+2];
+            }
+        }
+        // band of the inverse (Hutchinson-de Hoog)
+        let mut s0 = vec![zero; m];
+        let mut s1 = vec![zero; m];
+        let mut s2 = vec![zero; m];
+        for i in (0..m).rev() {
+            let (c11, c12, c22) = (
+                if i + 1 < m { s0[i+1] } else { zero },
+                if i + 2 < m { s1[i+1] } else { zero },
+                if i + 2 < m { s0[i+2] } else { zero },
+            );
+            s2[i] = zero - l1[i] * c12 - l2[i] * c22;
+            s1[i] = zero - l1[i] * c11 - l2[i] * c12;
+            s0[i] = one / d[i] - l1[i] * s1[i] - l2[i] * s2[i];
+        }
+        let sinv = |j: usize, k: usize| {
+            let (a, b) = if j < k { (j, k) } else { (k, j) };
+            match b - a {
+                0 => s0[a],
+                1 => s1[a],
+                2 => s2[a],
+                _ => zero,
+            }
+        };
+        let cols = |i: usize| i.saturating_sub(2)..(i + 1).min(m);
+        let g: Vec<F> = (0..n).map(|i| {
+            y[i] - lambda * v[i] * cols(i).fold(zero, |s, j| s + q(i, j) * gamma[j])
+        }).collect();
+        let tr = (0..n).fold(zero, |s, i| {
+            s + v[i] * cols(i).fold(zero, |s1, j| s1 + cols(i).fold(zero, |s2, k| s2 + q(i, j) * q(i, k) * sinv(j, k)))
+        }) * lambda;
+        let nf: F = F::from(n).unwrap();
+        let edf = nf - tr;
+        let rss = (0..n).fold(zero, |s, i| s + w[i] * (y[i] - g[i]) * (y[i] - g[i]));
+        let gcv = rss / nf / ((one - edf / nf) * (one - edf / nf));
+        // slopes of the natural cubic with values g and second derivatives gamma
+        let c2 = |i: usize| if i == 0 || i == n - 1 { zero } else { gamma[i-1] };
+        let mut dy: Vec<F> = (0..n-1).map(|i| (g[i+1] - g[i]) / h[i] - h[i] * (two * c2(i) + c2(i+1)) / six).collect();
+        dy.push((g[n-1] - g[n-2]) / h[n-2] + h[n-2] * (c2(n-2) + two * c2(n-1)) / six);
+        let abc = hermite_coeff(x, &g, &dy);
+        let spline = Spline3d { x: x.clone(), y: g, abc, ext: Extrapolation::Error };
+        Self { lambda, edf, gcv, spline }
+    }
+
+    pub fn with_extrapolation(mut self, ext: Extrapolation) -> Self {
+        self.spline.ext = ext;
+        self
     }
 
     pub fn calc(&self, z: F) -> Result<F, &str> {
-        let n = self.x.len();
-        match self.x.binary_search_by(|xi| xi.partial_cmp(&z).unwrap()) {
-            Ok(index) => Ok(self.y[index]),
-            Err(index) => {
-                if index == 0 || index == n {
-                    Err("out of range!")
-                } else {
-                    let (a, b, c) = self.abc[index-1];
-                    let mut m: F = One::one();
-                    let mut ans = self.y[index-1];
-                    m = m * (z - self.x[index-1]);
-                    ans = ans + c * m;
-                    m = m * (z - self.x[index-1]);
-                    ans = ans + b * m;
-                    m = m * (z - self.x[index-1]);
-                    ans = ans + a * m;
-                    Ok(ans)
-                }
+        self.spline.calc(z)
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Result<Array<F, D>, &'static str> {
+        self.spline.calc_array(z)
+    }
+}
+
+/// minimum of score over the grid ps, refined by golden section search
+/// between the neighbours of the best grid point
+fn scan_min<F: Float, G: Fn(F) -> F>(ps: &Vec<F>, score: G) -> F {
+    let np = ps.len();
+    let scores: Vec<F> = ps.iter().map(|&p| score(p)).collect();
+    let best = (0..np).fold(0, |m, i| if scores[i] < scores[m] { i } else { m });
+    let (mut a, mut b) = (ps[best.max(1) - 1], ps[(best + 1).min(np - 1)]);
+    let g: F = F::from((5f64.sqrt() - 1.0) / 2.0).unwrap();
+    let mut c = b - g * (b - a);
+    let mut d = a + g * (b - a);
+    let (mut fc, mut fd) = (score(c), score(d));
+    for _ in 0..40 {
+        if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - g * (b - a);
+            fc = score(c);
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + g * (b - a);
+            fd = score(d);
+        }
+    }
+    if fc.min(fd) < scores[best] { if fc < fd { c } else { d } } else { ps[best] }
+}
+
+/// sort the points by x and merge the ones with the same x into their weighted mean
+fn merge_points<F: Float>(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>) -> (Vec<F>, Vec<F>, Vec<F>) {
+    let zero: F = Zero::zero();
+    if x.len() != y.len() || x.len() != w.len() {
+        panic!("x, y and w have different number of elements!");
+    }
+    if w.iter().any(|&wi| !(wi > zero && wi.is_finite())) {
+        panic!("w should be positive!");
+    }
+    let mut p: Vec<(F, F, F)> = (0..x.len()).map(|i| (x[i], y[i], w[i])).collect();
+    p.sort_by(|a, b| (a.0).partial_cmp(&b.0).unwrap());
+    let (mut xs, mut ys, mut ws): (Vec<F>, Vec<F>, Vec<F>) = (Vec::new(), Vec::new(), Vec::new());
+    for (xi, yi, wi) in p.into_iter() {
+        if xs.last() == Some(&xi) {
+            let k = ws.len() - 1;
+            ys[k] = (ys[k] * ws[k] + yi * wi) / (ws[k] + wi);
+            ws[k] = ws[k] + wi;
+        } else {
+            xs.push(xi);
+            ys.push(yi);
+            ws.push(wi);
+        }
+    }
+    if xs.len() < 3 {
+        panic!("at least 3 distinct x are required!");
+    }
+    (xs, ys, ws)
+}
+
+/// traces of R and Q^T W^-1 Q, whose ratio sets the scale of lambda
+fn scales<F: Float>(x: &Vec<F>, w: &Vec<F>) -> (F, F) {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let three: F = F::from(3).unwrap();
+    let n = x.len();
+    let h: Vec<F> = (0..n-1).map(|i| x[i+1] - x[i]).collect();
+    let r = (0..n-2).fold(zero, |s, j| s + (h[j] + h[j+1]) / three);
+    let q = (0..n-2).fold(zero, |s, j| {
+        let (a, c) = (one / h[j], one / h[j+1]);
+        s + a * a / w[j] + (a + c) * (a + c) / w[j+1] + c * c / w[j+2]
+    });
+    (r, q)
+}
+
+/// sort the points by x, rejecting duplicates
+pub fn sort_points<F: Float, T: Copy>(x: &Vec<F>, y: &Vec<T>) -> (Vec<F>, Vec<T>) {
+    let n = x.len();
+    if n != y.len() {
+        panic!("x and y have different number of elements!");
+    }
+    if n < 2 {
+        panic!("length of x should be 2 or more!");
+    }
+    let mut xy: Vec<(F, T)> = x.iter().zip(y.iter()).map(|(&xi, &yi)| (xi, yi)).collect();
+    xy.sort_by(|a, b| (a.0).partial_cmp(&b.0).unwrap());
+    if (0..n-1).any(|i| xy[i].0 == xy[i+1].0) {
+        panic!("x should not contain duplicates!");
+    }
+    xy.into_iter().unzip()
+}
+
+/// widths and slopes of the segments of sorted points
+fn secants<F: Float>(x: &Vec<F>, y: &Vec<F>) -> (Vec<F>, Vec<F>) {
+    let h: Vec<F> = (0..x.len()-1).map(|i| x[i+1] - x[i]).collect();
+    let u: Vec<F> = (0..x.len()-1).map(|i| (y[i+1] - y[i]) / h[i]).collect();
+    (h, u)
+}
+
+fn sign<F: Float>(v: F) -> F {
+    if v > F::zero() {
+        F::one()
+    } else if v < F::zero() {
+        -F::one()
+    } else {
+        F::zero()
+    }
+}
+
+/// first derivatives at the sorted knots of the cubic spline with boundary condition `bc`
+pub fn spline_slopes<F: Float>(x: &Vec<F>, y: &Vec<F>, bc: SplineBoundary<F>) -> Vec<F> {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let three: F = two + one;
+    let n = x.len();
+    let (h, u) = secants(x, y);
+    if let SplineBoundary::Periodic = bc {
+        if y[0] != y[n-1] {
+            panic!("y[0] and y[n-1] should be equal for periodic spline!");
+        }
+    }
+    if n == 2 {
+        return match bc {
+            SplineBoundary::Clamped(c0, cn) => vec![c0, cn],
+            SplineBoundary::SecondDerivative(m0, mn) => {
+                let c0 = u[0] - h[0] * (two * m0 + mn) / F::from(6).unwrap();
+                let cn = u[0] + h[0] * (m0 + two * mn) / F::from(6).unwrap();
+                vec![c0, cn]
+            },
+            SplineBoundary::Periodic => vec![zero, zero],
+            _ => vec![u[0], u[0]],
+        };
+    }
+    // interior rows: h_i c_{i-1} + 2(h_{i-1} + h_i) c_i + h_{i-1} c_{i+1} = 3(h_i u_{i-1} + h_{i-1} u_i)
+    let mut dl: Vec<F> = (0..n-1).map(|i| if i == n-2 { zero } else { h[i+1] }).collect();
+    let mut d: Vec<F> = (0..n).map(|i| if i == 0 || i == n-1 { one } else { two * (h[i-1] + h[i]) }).collect();
+    let mut du: Vec<F> = (0..n-1).map(|i| if i == 0 { zero } else { h[i-1] }).collect();
+    let mut b: Vec<F> = (0..n).map(|i| {
+        if i == 0 || i == n-1 { zero } else { three * (h[i] * u[i-1] + h[i-1] * u[i]) }
+    }).collect();
+    match bc {
+        SplineBoundary::Clamped(c0, cn) => {
+            b[0] = c0;
+            b[n-1] = cn;
+        },
+        SplineBoundary::Natural => return spline_slopes(x, y, SplineBoundary::SecondDerivative(zero, zero)),
+        SplineBoundary::SecondDerivative(m0, mn) => {
+            d[0] = two;
+            du[0] = one;
+            b[0] = three * u[0] - m0 * h[0] / two;
+            dl[n-2] = one;
+            d[n-1] = two;
+            b[n-1] = three * u[n-2] + mn * h[n-2] / two;
+        },
+        SplineBoundary::NotAKnot => {
+            if n == 3 {
+                // the parabola through the 3 points
+                let f012 = (u[1] - u[0]) / (x[2] - x[0]);
+                return x.iter().map(|&xi| u[0] + f012 * (two * xi - x[0] - x[1])).collect();
             }
+            let x31 = h[0] + h[1];
+            d[0] = h[1];
+            du[0] = x31;
+            b[0] = ((h[0] + two * x31) * h[1] * u[0] + h[0] * h[0] * u[1]) / x31;
+            let xn = h[n-2] + h[n-3];
+            dl[n-2] = xn;
+            d[n-1] = h[n-3];
+            b[n-1] = (h[n-2] * h[n-2] * u[n-3] + (two * xn + h[n-2]) * h[n-3] * u[n-2]) / xn;
+        },
+        SplineBoundary::Periodic => {
+            // unknowns c_0 .. c_{n-2} with c_{n-1} = c_0, row 0 wraps to the last segment
+            let m = n - 1;
+            let hp = h[m-1];
+            let up = u[m-1];
+            d[0] = two * (hp + h[0]);
+            b[0] = three * (h[0] * up + hp * u[0]);
+            let mut c = if m == 2 {
+                solve_tridiagonal(&vec![h[1] + h[0]], &vec![d[0], d[1]], &vec![h[0] + hp], &vec![b[0], b[1]])
+            } else {
+                du[0] = hp;
+                solve_cyclic_tridiagonal(&dl[..m-1].to_vec(), &d[..m].to_vec(), &du[..m-1].to_vec(),
+                                         h[0], h[m-2], &b[..m].to_vec())
+            };
+            c.push(c[0]);
+            return c;
+        },
+    }
+    solve_tridiagonal(&dl, &d, &du, &b)
+}
+
+/// Thomas algorithm for the tridiagonal system with the sub-diagonal dl,
+/// the diagonal d and the super-diagonal du
+pub fn solve_tridiagonal<F: Float>(dl: &Vec<F>, d: &Vec<F>, du: &Vec<F>, b: &Vec<F>) -> Vec<F> {
+    let n = d.len();
+    let mut cp = du.clone();
+    let mut dp = b.clone();
+    let mut den = d[0];
+    if n > 1 {
+        cp[0] = du[0] / den;
+    }
+    dp[0] = b[0] / den;
+    for i in 1..n {
+        den = d[i] - dl[i-1] * cp[i-1];
+        if i < n-1 {
+            cp[i] = du[i] / den;
         }
+        dp[i] = (b[i] - dl[i-1] * dp[i-1]) / den;
     }
+    for i in (0..n-1).rev() {
+        dp[i] = dp[i] - cp[i] * dp[i+1];
+    }
+    dp
+}
+
+/// cyclic tridiagonal system (n >= 3) with `upper` at (0, n-1) and `lower` at (n-1, 0),
+/// solved by the Sherman-Morrison formula
+pub fn solve_cyclic_tridiagonal<F: Float>(dl: &Vec<F>, d: &Vec<F>, du: &Vec<F>, upper: F, lower: F, b: &Vec<F>) -> Vec<F> {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let n = d.len();
+    let gamma = -d[0];
+    let mut bb = d.clone();
+    bb[0] = d[0] - gamma;
+    bb[n-1] = d[n-1] - lower * upper / gamma;
+    let x = solve_tridiagonal(dl, &bb, du, b);
+    let mut v = vec![zero; n];
+    v[0] = gamma;
+    v[n-1] = lower;
+    let z = solve_tridiagonal(dl, &bb, du, &v);
+    let fact = (x[0] + upper * x[n-1] / gamma) / (one + z[0] + upper * z[n-1] / gamma);
+    x.iter().zip(z.iter()).map(|(&xi, &zi)| xi - fact * zi).collect()
+}
+
+/// coefficients (a, b, c) of y_i + c t + b t^2 + a t^3 (t = x - x_i)
+/// matching values and slopes at both ends of each segment
+pub fn hermite_coeff<F: Float>(x: &Vec<F>, y: &Vec<F>, dy: &Vec<F>) -> Vec<(F, F, F)> {
+    let one: F = One::one();
+    let two: F = one + one;
+    let three: F = two + one;
+    (0..x.len()-1).map(|i| {
+        let h = x[i+1] - x[i];
+        let u = (y[i+1] - y[i]) / h;
+        let b = (three * u - two * dy[i] - dy[i+1]) / h;
+        let a = (dy[i] + dy[i+1] - two * u) / (h * h);
+        (a, b, dy[i])
+    }).collect()
+}
+
+/// Goodness of fit and parameter uncertainties of a weighted least squares fit with k parameters
+pub struct FitStats<F: Float> {
+    /// y - f(x)
+    pub residuals: Vec<F>,
+    /// sum_i w[i] r[i]^2 (chi^2 for w = 1 / sigma^2)
+    pub rss: F,
+    /// 1 - RSS / TSS about the weighted mean of y
+    pub r2: F,
+    /// 1 - (1 - R^2) (n - 1) / (n - k)
+    pub adj_r2: F,
+    /// degrees of freedom n - k, 0 if the fit is exactly determined
+    pub dof: F,
+    /// RSS / (n - k), NaN if dof is 0
+    pub reduced_chi2: F,
+    /// covariance of the parameters scaled by reduced_chi2 (weights relative),
+    /// divide by reduced_chi2 if the weights are the absolute 1 / sigma^2
+    pub covariance: Array2<F>,
+    pub std_errors: Vec<F>,
+    /// n ln(RSS / n) + 2k
+    pub aic: F,
+    /// n ln(RSS / n) + k ln(n)
+    pub bic: F,
 }
 
+impl<F: Float> FitStats<F> {
+    /// statistics of the fitted values with the covariance for unit variance of the weighted data
+    pub fn new(y: &Vec<F>, fitted: &Vec<F>, w: &Vec<F>, covariance: Array2<F>, k: F) -> Self {
+        let zero: F = Zero::zero();
+        let one: F = One::one();
+        let n = y.len();
+        if n != fitted.len() || n != w.len() {
+            panic!("y, fitted and w have different number of elements!");
+        }
+        let nf = F::from(n).unwrap();
+        let residuals: Vec<F> = y.iter().zip(fitted.iter()).map(|(&yi, &fi)| yi - fi).collect();
+        let rss = residuals.iter().zip(w.iter()).fold(zero, |m, (&r, &wi)| m + wi * r * r);
+        let sw = w.iter().fold(zero, |m, &wi| m + wi);
+        let mean = y.iter().zip(w.iter()).fold(zero, |m, (&yi, &wi)| m + wi * yi) / sw;
+        let tss = y.iter().zip(w.iter()).fold(zero, |m, (&yi, &wi)| m + wi * (yi - mean) * (yi - mean));
+        let r2 = one - rss / tss;
+        // an exactly determined fit (up to the rounding of a fractional k) has no residual variance
+        let dof = if nf - k > nf * F::epsilon().sqrt() { nf - k } else { zero };
+        let reduced_chi2 = if dof > zero { rss / dof } else { F::nan() };
+        let covariance = covariance.mapv(|v| v * reduced_chi2);
+        let std_errors = covariance.diag().iter().map(|&v| v.sqrt()).collect();
+        Self {
+            residuals, rss, r2,
+            adj_r2: if dof > zero { one - (one - r2) * (nf - one) / dof } else { F::nan() },
+            dof, reduced_chi2, covariance, std_errors,
+            aic: nf * (rss / nf).ln() + (k + k),
+            bic: nf * (rss / nf).ln() + k * nf.ln(),
+        }
+    }
+}
+
+impl<F: Float + FloatConst> FitStats<F> {
+    /// interval of the given level (e.g. 0.95) around the fitted value with the gradient
+    /// of the model with respect to the parameters, widened by the variance of an observation
+    /// with unit weight if prediction; Err if the fit has no degrees of freedom
+    pub fn interval(&self, value: F, grad: &Vec<F>, level: F, prediction: bool) -> Result<(F, F), &'static str> {
+        let zero: F = Zero::zero();
+        let one: F = One::one();
+        if !(self.dof > zero) {
+            return Err("no degrees of freedom!");
+        }
+        let nf = grad.len();
+        let mut var = (0..nf).fold(zero, |m, i| {
+            (0..nf).fold(m, |m, j| m + grad[i] * self.covariance[[i, j]] * grad[j])
+        });
+        if prediction {
+            var = var + self.reduced_chi2;
+        }
+        let t = student_t_quantile((one + level) / (one + one), self.dof);
+        let h = t * var.sqrt();
+        Ok((value - h, value + h))
+    }
+}
+
+/// Linear least squares y = sum_j coeff[j] items[j](x) minimizing
+/// sum_i w[i] r[i]^2 + lambda |L coeff|^2, solved on the (augmented) design matrix
+/// by the SVD (LAPACK gelsd) without forming the normal equations
 pub struct LSM<F: Float + Lapack> {
     pub x: Vec<F>,
     pub y: Vec<F>,
     pub items: Vec<fn(F)->F>,
     pub coeff: Vec<F>,
+    /// weights of the points, 1 / sigma^2
+    pub w: Vec<F>,
+    /// regularization parameter and penalty matrix L (identity if None)
+    pub lambda: F,
+    pub penalty: Option<Array2<F>>,
+    /// trace of the influence matrix (effective degrees of freedom)
+    pub edf: F,
+    /// effective rank of the design matrix, less than items.len() if the items are
+    /// linearly dependent on x (coeff is then the minimum norm solution)
+    pub rank: usize,
+    /// singular values of the weighted (and augmented) design matrix in descending order
+    pub singular_values: Vec<F>,
+    pub stats: FitStats<F>,
 }
 
 impl<F: Float + Lapack> LSM<F> {
     pub fn new(x: &Vec<F>, y: &Vec<F>, items: Vec<fn(F)->F>) -> Self {
-        let n = x.len();
-        if n != y.len() {
-            panic!("x and y have different number of elements!");
+        Self::ridge(x, y, &vec![One::one(); x.len()], items, Zero::zero(), None)
+    }
+
+    /// weights w[i] = 1 / sigma_i^2
+    pub fn with_weights(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, items: Vec<fn(F)->F>) -> Self {
+        Self::ridge(x, y, w, items, Zero::zero(), None)
+    }
+
+    /// Tikhonov regularization with the penalty matrix L of items.len() columns
+    pub fn ridge(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, items: Vec<fn(F)->F>, lambda: F, penalty: Option<&Array2<F>>) -> Self {
+        if !(lambda >= Zero::zero()) {
+            panic!("lambda should be non-negative!");
         }
-        let nf = items.len();
-        if nf == 0 {
-            panic!("items should contain at least 1 function!");
+        let (a, b) = weighted_system(x, y, w, &items, penalty);
+        let l = penalty_matrix(penalty, items.len());
+        let (coeff, singular_values, rank, edf) = penalized(&a, &b, &l, lambda);
+        let fitted = x.iter().map(|&xi| items.iter().zip(coeff.iter()).fold(F::zero(), |m, (f, &c)| m + c * f(xi))).collect();
+        let stats = FitStats::new(y, &fitted, w, covariance(&a, &l, lambda, Some(rank)), edf);
+        Self {
+            x: x.clone(), y: y.clone(), items, coeff: coeff.to_vec(), w: w.clone(),
+            lambda, penalty: penalty.cloned(), edf, rank, singular_values, stats,
         }
-        let mut a = Array2::zeros((n, nf));
-        let mut b = Array1::zeros(n);
-        for i in 0..n {
-            for j in 0..nf {
-                a[[i, j]] = items[j](x[i]);
+    }
+
+    /// lambda minimizing the generalized cross-validation score n RSS / (n - edf)^2,
+    /// found by a scan over 24 decades followed by golden section search on log(lambda)
+    pub fn gcv(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, items: Vec<fn(F)->F>, penalty: Option<&Array2<F>>) -> Self {
+        let (a, b) = weighted_system(x, y, w, &items, penalty);
+        let l = penalty_matrix(penalty, items.len());
+        let ten: F = F::from(10).unwrap();
+        let nf = F::from(a.nrows()).unwrap();
+        let score = |p: F| {
+            let (coeff, _, _, edf) = penalized(&a, &b, &l, ten.powf(p));
+            let r = a.dot(&coeff) - &b;
+            nf * r.dot(&r) / ((nf - edf) * (nf - edf))
+        };
+        let p = scan_min(&lambda_grid(&a, &l, 2), score);
+        Self::ridge(x, y, w, items, ten.powf(p), penalty)
+    }
+
+    /// lambda at the corner (largest curvature) of the L-curve (log |sqrt(W) r|, log |L coeff|),
+    /// scanned over 24 decades; the choice of `gcv` if the curve has no corner
+    pub fn lcurve(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, items: Vec<fn(F)->F>, penalty: Option<&Array2<F>>) -> Self {
+        let zero: F = Zero::zero();
+        let two: F = F::from(2).unwrap();
+        let (a, b) = weighted_system(x, y, w, &items, penalty);
+        let l = penalty_matrix(penalty, items.len());
+        let ten: F = F::from(10).unwrap();
+        let ps = lambda_grid(&a, &l, 4);
+        let curve: Vec<(F, F)> = ps.iter().map(|&p| {
+            let (coeff, _, _, _) = penalized(&a, &b, &l, ten.powf(p));
+            let r = a.dot(&coeff) - &b;
+            let lc = l.dot(&coeff);
+            (Float::ln(r.dot(&r)) / two, Float::ln(lc.dot(&lc)) / two)
+        }).collect();
+        // signed Menger curvature of consecutive points, the corner turns counterclockwise
+        let mut best: Option<(usize, F)> = None;
+        for i in 1..ps.len()-1 {
+            let (p, q, r) = (curve[i-1], curve[i], curve[i+1]);
+            let cross = (q.0 - p.0) * (r.1 - q.1) - (q.1 - p.1) * (r.0 - q.0);
+            let d = |u: (F, F), v: (F, F)| Float::sqrt((u.0 - v.0) * (u.0 - v.0) + (u.1 - v.1) * (u.1 - v.1));
+            let k = two * cross / (d(p, q) * d(q, r) * d(p, r));
+            if k > best.map_or(zero, |b| b.1) {
+                best = Some((i, k));
             }
-            b[i] = y[i];
         }
-        let taa = a.t().dot(&a);
-        let tab = b.dot(&a);
-        let coeff = taa.solve(&tab).unwrap().to_vec();
+        match best {
+            Some((i, _)) => Self::ridge(x, y, w, items, ten.powf(ps[i]), penalty),
+            None => Self::gcv(x, y, w, items, penalty),
+        }
+    }
+
+    pub fn rank_deficient(&self) -> bool {
+        self.rank < self.items.len()
+    }
 
-        Self { x: x.clone(), y: y.clone(), items, coeff, }
+    /// ratio of the largest to the smallest singular value, infinite if rank deficient
+    pub fn cond(&self) -> F {
+        if self.rank_deficient() {
+            return F::infinity();
+        }
+        self.singular_values[0] / self.singular_values[self.items.len() - 1]
     }
 
     pub fn calc(&self, z: F) -> F {
         let zero: F = Zero::zero();
         self.items.iter().zip(self.coeff.iter()).map(|(&i, &c)| c * i(z)).fold(zero, |m, j| m + j)
     }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Array<F, D> {
+        z.mapv(|zi| self.calc(zi))
+    }
+}
+
+impl<F: Float + FloatConst + Lapack> LSM<F> {
+    /// confidence interval of the fitted curve at z, e.g. level = 0.95
+    pub fn confidence_interval(&self, z: F, level: F) -> Result<(F, F), &'static str> {
+        let grad = self.items.iter().map(|f| f(z)).collect();
+        self.stats.interval(self.calc(z), &grad, level, false)
+    }
+
+    /// prediction interval of a new observation at z with unit weight
+    pub fn prediction_interval(&self, z: F, level: F) -> Result<(F, F), &'static str> {
+        let grad = self.items.iter().map(|f| f(z)).collect();
+        self.stats.interval(self.calc(z), &grad, level, true)
+    }
+}
+
+/// Loss functions of robust regression with the tuning constant c in units of the residual scale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RobustLoss<F: Float> {
+    /// quadratic within c, linear outside
+    Huber(F),
+    /// Tukey's bisquare, points beyond c are rejected
+    Tukey(F),
+    /// Cauchy (Lorentzian)
+    Cauchy(F),
+}
+
+impl<F: Float> RobustLoss<F> {
+    /// c = 1.345, 95% efficiency for normal errors
+    pub fn huber() -> Self {
+        RobustLoss::Huber(F::from(1.345).unwrap())
+    }
+
+    /// c = 4.685, 95% efficiency for normal errors
+    pub fn tukey() -> Self {
+        RobustLoss::Tukey(F::from(4.685).unwrap())
+    }
+
+    /// c = 2.385, 95% efficiency for normal errors
+    pub fn cauchy() -> Self {
+        RobustLoss::Cauchy(F::from(2.385).unwrap())
+    }
+
+    /// IRLS weight psi(u) / u of the scaled residual u
+    pub fn weight(&self, u: F) -> F {
+        let zero: F = Zero::zero();
+        let one: F = One::one();
+        let u = u.abs();
+        match *self {
+            RobustLoss::Huber(c) => if u <= c { one } else { c / u },
+            RobustLoss::Tukey(c) => {
+                if u < c {
+                    let t = one - (u / c) * (u / c);
+                    t * t
+                } else {
+                    zero
+                }
+            },
+            RobustLoss::Cauchy(c) => one / (one + (u / c) * (u / c)),
+        }
+    }
+}
+
+/// Robust linear least squares by iteratively reweighted least squares (IRLS),
+/// the residuals are scaled by the MAD estimate 1.4826 median|r - median(r)| of each iteration
+pub struct RobustLSM<F: Float + Lapack> {
+    /// weighted fit of the last iteration, with the weights w[i] weights[i]
+    pub lsm: LSM<F>,
+    pub loss: RobustLoss<F>,
+    /// robust scale of the residuals
+    pub scale: F,
+    /// robustness weights in [0, 1], small for outliers
+    pub weights: Vec<F>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+impl<F: Float + Lapack> RobustLSM<F> {
+    pub fn new(x: &Vec<F>, y: &Vec<F>, items: Vec<fn(F)->F>, loss: RobustLoss<F>) -> Self {
+        Self::with_weights(x, y, &vec![One::one(); x.len()], items, loss)
+    }
+
+    /// prior weights w[i] = 1 / sigma_i^2, at most 100 iterations until the relative change
+    /// of the coefficients is below 1e-8
+    pub fn with_weights(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, items: Vec<fn(F)->F>, loss: RobustLoss<F>) -> Self {
+        let zero: F = Zero::zero();
+        let n = x.len();
+        let tol = F::from(1e-8).unwrap();
+        let mut lsm = LSM::with_weights(x, y, w, items.clone());
+        let floor = (0..n).fold(zero, |m, i| m.max(Float::abs(y[i] * Float::sqrt(w[i])))) * F::epsilon() * F::from(n).unwrap();
+        let mut weights = vec![F::one(); n];
+        let mut scale = zero;
+        let mut converged = false;
+        let mut iterations = 0;
+        while iterations < 100 {
+            iterations += 1;
+            let r: Vec<F> = (0..n).map(|i| (y[i] - lsm.calc(x[i])) * Float::sqrt(w[i])).collect();
+            scale = mad(&r);
+            // the scale is kept above the rounding level, e.g. for exactly fitted points,
+            // and rejected points keep a tiny weight so that the system stays the same size
+            let s = scale.max(floor).max(F::min_positive_value());
+            weights = r.iter().map(|&ri| loss.weight(ri / s).max(F::epsilon())).collect();
+            let wr: Vec<F> = (0..n).map(|i| w[i] * weights[i]).collect();
+            let next = LSM::with_weights(x, y, &wr, items.clone());
+            let cmax = next.coeff.iter().fold(zero, |m, &c| m.max(Float::abs(c)));
+            let change = next.coeff.iter().zip(lsm.coeff.iter()).fold(zero, |m, (&a, &b)| m.max(Float::abs(a - b)));
+            lsm = next;
+            if change <= tol * (cmax + tol) {
+                converged = true;
+                break;
+            }
+        }
+        Self { lsm, loss, scale, weights, iterations, converged }
+    }
+
+    pub fn calc(&self, z: F) -> F {
+        self.lsm.calc(z)
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Array<F, D> {
+        self.lsm.calc_array(z)
+    }
+
+    /// indices of the points whose robustness weight is below threshold
+    pub fn outliers(&self, threshold: F) -> Vec<usize> {
+        (0..self.weights.len()).filter(|&i| self.weights[i] < threshold).collect()
+    }
+}
+
+/// 1.4826 median|r - median(r)|, consistent with the standard deviation for normal errors
+pub fn mad<F: Float>(r: &Vec<F>) -> F {
+    let med = median(r);
+    let dev: Vec<F> = r.iter().map(|&ri| (ri - med).abs()).collect();
+    F::from(1.4826).unwrap() * median(&dev)
+}
+
+pub fn median<F: Float>(v: &Vec<F>) -> F {
+    let mut s = v.clone();
+    s.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = s.len();
+    if n % 2 == 1 { s[n / 2] } else { (s[n / 2 - 1] + s[n / 2]) / F::from(2).unwrap() }
+}
+
+/// design matrix and right-hand side scaled by sqrt(w)
+fn weighted_system<F: Float>(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, items: &Vec<fn(F)->F>, penalty: Option<&Array2<F>>) -> (Array2<F>, Array1<F>) {
+    let n = x.len();
+    if n != y.len() || n != w.len() {
+        panic!("x, y and w have different number of elements!");
+    }
+    if items.is_empty() {
+        panic!("items should contain at least 1 function!");
+    }
+    if w.iter().any(|&wi| !(wi > Zero::zero() && wi.is_finite())) {
+        panic!("weights should be positive!");
+    }
+    if let Some(l) = penalty {
+        if l.ncols() != items.len() {
+            panic!("penalty should have items.len() columns!");
+        }
+    }
+    let sw: Vec<F> = w.iter().map(|&wi| wi.sqrt()).collect();
+    let a = Array2::from_shape_fn((n, items.len()), |(i, j)| sw[i] * items[j](x[i]));
+    let b = Array1::from_shape_fn(n, |i| sw[i] * y[i]);
+    (a, b)
+}
+
+fn penalty_matrix<F: Float>(penalty: Option<&Array2<F>>, nf: usize) -> Array2<F> {
+    match penalty {
+        Some(l) => l.clone(),
+        None => Array2::eye(nf),
+    }
+}
+
+/// (coeff, singular values, rank, edf) of the least squares [a; sqrt(lambda) l] coeff = [b; 0]
+fn penalized<F: Float + Lapack>(a: &Array2<F>, b: &Array1<F>, l: &Array2<F>, lambda: F) -> (Array1<F>, Vec<F>, usize, F) {
+    let zero: F = Zero::zero();
+    let (n, nf) = a.dim();
+    let p = if lambda > zero { l.nrows() } else { 0 };
+    let m = augmented(a, l, lambda);
+    // with the right-hand side a the solution is (M^T M)^-1 A^T A, whose trace is that of the influence matrix
+    let mut r: Array2<F> = Array2::zeros((n + p, nf + 1));
+    r.slice_mut(s![..n, 0]).assign(b);
+    r.slice_mut(s![..n, 1..]).assign(a);
+    let res = m.least_squares(&r).unwrap();
+    let coeff = res.solution.column(0).to_owned();
+    let edf = (0..nf).fold(zero, |t, j| t + res.solution[[j, j+1]]);
+    let sv = res.singular_values.iter().map(|&v| <F as NumCast>::from(v).unwrap()).collect();
+    (coeff, sv, res.rank as usize, edf)
+}
+
+/// [a; sqrt(lambda) l], or a if lambda is zero
+fn augmented<F: Float>(a: &Array2<F>, l: &Array2<F>, lambda: F) -> Array2<F> {
+    if lambda == Zero::zero() {
+        return a.clone();
+    }
+    let n = a.nrows();
+    let sl = lambda.sqrt();
+    let mut m: Array2<F> = Array2::zeros((n + l.nrows(), a.ncols()));
+    m.slice_mut(s![..n, ..]).assign(a);
+    m.slice_mut(s![n.., ..]).assign(&l.mapv(|v| v * sl));
+    m
+}
+
+/// covariance of the coefficients for unit variance of the weighted data,
+/// Q A^T A Q with Q = (M^T M)^+ = V S^-2 V^T on the rank largest singular values of M = [a; sqrt(lambda) l]
+/// (those above max(rows, cols) eps s_max if rank is None)
+pub fn covariance<F: Float + Lapack>(a: &Array2<F>, l: &Array2<F>, lambda: F, rank: Option<usize>) -> Array2<F> {
+    let nf = a.ncols();
+    let m = augmented(a, l, lambda);
+    let (_, sv, vt) = m.svd(false, true).unwrap();
+    let vt = vt.unwrap();
+    let sv: Vec<F> = sv.iter().map(|&v| <F as NumCast>::from(v).unwrap()).collect();
+    let rank = match rank {
+        Some(r) => r.min(sv.len()),
+        None => {
+            let tol = F::from(m.nrows().max(nf)).unwrap() * F::epsilon() * sv[0];
+            sv.iter().filter(|&&v| v > tol).count()
+        },
+    };
+    let mut q: Array2<F> = Array2::zeros((nf, nf));
+    for (k, &s) in sv.iter().enumerate().take(rank) {
+        let v = vt.row(k);
+        for i in 0..nf {
+            for j in 0..nf {
+                q[[i, j]] = q[[i, j]] + v[i] * v[j] / (s * s);
+            }
+        }
+    }
+    let c = a.dot(&q);
+    c.t().dot(&c)
+}
+
+/// log10(lambda) grid over 24 decades around the ratio of the squared norms of a and l,
+/// with `per` points per decade
+fn lambda_grid<F: Float>(a: &Array2<F>, l: &Array2<F>, per: usize) -> Vec<F> {
+    let zero: F = Zero::zero();
+    let na = a.iter().fold(zero, |m, &v| m + v * v);
+    let nl = l.iter().fold(zero, |m, &v| m + v * v);
+    let p0 = (na / nl).log10();
+    let step = F::from(per).unwrap();
+    (0..24*per+1).map(|i| p0 + F::from(i).unwrap() / step - F::from(12).unwrap()).collect()
 }
\ No newline at end of file
@@ -0,0 +1,247 @@
+use num_traits::float::Float;
+use num_traits::{Zero, One};
+use ndarray::*;
+use crate::fit::{segment, spline_slopes, SplineBoundary};
+use crate::interpolation::Extrapolation;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GridMethod {
+    /// bilinear / trilinear
+    Linear,
+    /// Keys cubic convolution (Catmull-Rom), slopes by three-point differences
+    /// (central inside, one-sided at the ends)
+    CubicConvolution,
+    /// bicubic / tricubic tensor-product not-a-knot spline
+    Spline,
+}
+
+/// Interpolation of z[[i, j]] = f(x[i], y[j]) on a rectilinear grid
+pub struct Grid2d<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub z: Array2<F>,
+    pub method: GridMethod,
+    /// extrapolation along x and y
+    pub ext: [Extrapolation; 2],
+    /// z differentiated along the axes in the bitmask of the index (empty for Linear)
+    pub d: Vec<ArrayD<F>>,
+}
+
+impl<F: Float> Grid2d<F> {
+    pub fn new(x: &Vec<F>, y: &Vec<F>, z: &Array2<F>, method: GridMethod) -> Self {
+        if z.dim() != (x.len(), y.len()) {
+            panic!("shape of z should be (x.len(), y.len())!");
+        }
+        let d = tables(&[x, y], z.clone().into_dyn(), method);
+        Self { x: x.clone(), y: y.clone(), z: z.clone(), method, ext: [Extrapolation::Error; 2], d }
+    }
+
+    /// extrapolation along each axis
+    pub fn with_extrapolation(mut self, ext: [Extrapolation; 2]) -> Self {
+        self.ext = ext;
+        self
+    }
+
+    pub fn calc(&self, x: F, y: F) -> Result<F, &str> {
+        grid_calc(&[&self.x, &self.y], &self.z.view().into_dyn(), &self.d, &[x, y], &self.ext, self.method)
+    }
+
+    /// calc at the points (x[idx], y[idx]) of arrays with the same shape
+    pub fn calc_array<D: Dimension>(&self, x: &Array<F, D>, y: &Array<F, D>) -> Result<Array<F, D>, &str> {
+        if x.shape() != y.shape() {
+            return Err("x and y should have the same shape!");
+        }
+        let mut ret = Vec::with_capacity(x.len());
+        for (&xi, &yi) in x.iter().zip(y.iter()) {
+            ret.push(self.calc(xi, yi)?);
+        }
+        Ok(Array::from_shape_vec(x.raw_dim(), ret).unwrap())
+    }
+}
+
+/// Interpolation of v[[i, j, k]] = f(x[i], y[j], z[k]) on a rectilinear grid
+pub struct Grid3d<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub z: Vec<F>,
+    pub v: Array3<F>,
+    pub method: GridMethod,
+    /// extrapolation along x, y and z
+    pub ext: [Extrapolation; 3],
+    /// v differentiated along the axes in the bitmask of the index (empty for Linear)
+    pub d: Vec<ArrayD<F>>,
+}
+
+impl<F: Float> Grid3d<F> {
+    pub fn new(x: &Vec<F>, y: &Vec<F>, z: &Vec<F>, v: &Array3<F>, method: GridMethod) -> Self {
+        if v.dim() != (x.len(), y.len(), z.len()) {
+            panic!("shape of v should be (x.len(), y.len(), z.len())!");
+        }
+        let d = tables(&[x, y, z], v.clone().into_dyn(), method);
+        Self { x: x.clone(), y: y.clone(), z: z.clone(), v: v.clone(), method, ext: [Extrapolation::Error; 3], d }
+    }
+
+    /// extrapolation along each axis
+    pub fn with_extrapolation(mut self, ext: [Extrapolation; 3]) -> Self {
+        self.ext = ext;
+        self
+    }
+
+    pub fn calc(&self, x: F, y: F, z: F) -> Result<F, &str> {
+        grid_calc(&[&self.x, &self.y, &self.z], &self.v.view().into_dyn(), &self.d, &[x, y, z], &self.ext, self.method)
+    }
+
+    /// calc at the points (x[idx], y[idx], z[idx]) of arrays with the same shape
+    pub fn calc_array<D: Dimension>(&self, x: &Array<F, D>, y: &Array<F, D>, z: &Array<F, D>) -> Result<Array<F, D>, &str> {
+        if x.shape() != y.shape() || x.shape() != z.shape() {
+            return Err("x, y and z should have the same shape!");
+        }
+        let mut ret = Vec::with_capacity(x.len());
+        for ((&xi, &yi), &zi) in x.iter().zip(y.iter()).zip(z.iter()) {
+            ret.push(self.calc(xi, yi, zi)?);
+        }
+        Ok(Array::from_shape_vec(x.raw_dim(), ret).unwrap())
+    }
+}
+
+/// derivative tables d[mask] of the values differentiated once along each axis in mask
+fn tables<F: Float>(axes: &[&Vec<F>], v: ArrayD<F>, method: GridMethod) -> Vec<ArrayD<F>> {
+    for x in axes.iter() {
+        if x.len() < 2 {
+            panic!("length of the grid axes should be 2 or more!");
+        }
+        if (0..x.len()-1).any(|i| !(x[i] < x[i+1])) {
+            panic!("grid axes should be strictly increasing!");
+        }
+    }
+    if method == GridMethod::Linear {
+        return Vec::new();
+    }
+    let dim = axes.len();
+    let mut d: Vec<ArrayD<F>> = vec![v];
+    for mask in 1..1usize << dim {
+        // differentiate the table without the highest axis of mask along that axis
+        let a = (0..dim).rev().find(|&a| mask & (1 << a) != 0).unwrap();
+        let mut t = d[mask & !(1 << a)].clone();
+        for mut lane in t.lanes_mut(Axis(a)) {
+            let y: Vec<F> = lane.to_vec();
+            let s = match method {
+                GridMethod::Spline => spline_slopes(axes[a], &y, SplineBoundary::NotAKnot),
+                _ => difference_slopes(axes[a], &y),
+            };
+            for (o, si) in lane.iter_mut().zip(s) {
+                *o = si;
+            }
+        }
+        d.push(t);
+    }
+    d
+}
+
+/// slopes of the parabolas through three neighbouring points, one-sided at the ends
+fn difference_slopes<F: Float>(x: &Vec<F>, y: &Vec<F>) -> Vec<F> {
+    let n = x.len();
+    if n == 2 {
+        let u = (y[1] - y[0]) / (x[1] - x[0]);
+        return vec![u, u];
+    }
+    // derivative at x[i] of the parabola through the points j, j+1, j+2
+    let quad = |j: usize, i: usize| {
+        let (x0, x1, x2) = (x[j], x[j+1], x[j+2]);
+        let xi = x[i];
+        y[j] * ((xi - x1) + (xi - x2)) / ((x0 - x1) * (x0 - x2))
+            + y[j+1] * ((xi - x0) + (xi - x2)) / ((x1 - x0) * (x1 - x2))
+            + y[j+2] * ((xi - x0) + (xi - x1)) / ((x2 - x0) * (x2 - x1))
+    };
+    (0..n).map(|i| quad(i.max(1).min(n - 2) - 1, i)).collect()
+}
+
+/// (node, derivative order, weight) of the 1-D interpolant along one axis at q
+fn axis_weights<F: Float>(x: &Vec<F>, q: F, ext: Extrapolation, cubic: bool) -> Result<Vec<(usize, usize, F)>, &'static str> {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let three: F = two + one;
+    let n = x.len();
+    let (lo, hi) = (x[0], x[n-1]);
+    if q.is_nan() {
+        return Err("out of range!");
+    }
+    let mut q = q;
+    if q < lo || q > hi {
+        match ext {
+            Extrapolation::Error => return Err("out of range!"),
+            Extrapolation::Clamp => q = q.max(lo).min(hi),
+            Extrapolation::Periodic => {
+                if q.is_infinite() {
+                    return Err("out of range!");
+                }
+                let p = hi - lo;
+                let mut w = (q - lo) % p;
+                if w < zero {
+                    w = w + p;
+                }
+                q = (lo + w).min(hi);
+            },
+            Extrapolation::Linear if cubic => {
+                let e = if q < lo { 0 } else { n - 1 };
+                return Ok(vec![(e, 0, one), (e, 1, q - x[e])]);
+            },
+            _ => {},
+        }
+    }
+    let i = if q < lo { 0 } else if q > hi { n - 2 } else { segment(x, q)? };
+    let h = x[i+1] - x[i];
+    let t = (q - x[i]) / h;
+    if cubic {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        Ok(vec![
+            (i, 0, two * t3 - three * t2 + one),
+            (i, 1, h * (t3 - two * t2 + t)),
+            (i + 1, 0, three * t2 - two * t3),
+            (i + 1, 1, h * (t3 - t2)),
+        ])
+    } else {
+        Ok(vec![(i, 0, one - t), (i + 1, 0, t)])
+    }
+}
+
+/// tensor product of the 1-D interpolants along every axis
+fn grid_calc<F: Float>(axes: &[&Vec<F>], v: &ArrayViewD<F>, d: &Vec<ArrayD<F>>, q: &[F], ext: &[Extrapolation], method: GridMethod) -> Result<F, &'static str> {
+    let cubic = method != GridMethod::Linear;
+    let dim = axes.len();
+    let mut w = Vec::with_capacity(dim);
+    for a in 0..dim {
+        w.push(axis_weights(axes[a], q[a], ext[a], cubic)?);
+    }
+    let mut ans: F = Zero::zero();
+    let mut idx = vec![0usize; dim];
+    let mut k = vec![0usize; dim];
+    loop {
+        let mut weight: F = One::one();
+        let mut mask = 0;
+        for a in 0..dim {
+            let (node, order, wa) = w[a][k[a]];
+            idx[a] = node;
+            mask |= order << a;
+            weight = weight * wa;
+        }
+        let value = if cubic { d[mask][IxDyn(&idx)] } else { v[IxDyn(&idx)] };
+        ans = ans + weight * value;
+        // next combination
+        let mut a = 0;
+        while a < dim {
+            k[a] += 1;
+            if k[a] < w[a].len() {
+                break;
+            }
+            k[a] = 0;
+            a += 1;
+        }
+        if a == dim {
+            break;
+        }
+    }
+    Ok(ans)
+}
@@ -3,6 +3,24 @@ use num_traits::{Zero, One};
 use crate::polynomial::{pnx, zeta_vec};
 use std::collections::VecDeque;
 
+const XGK: [f64; 8] = [
+    0.991455371120812639206854697526329, 0.949107912342758524526189684047851,
+    0.864864423359769072789712788640926, 0.741531185599394439863864773280788,
+    0.586087235467691130294144845693013, 0.405845151377397166906606412076961,
+    0.207784955007898467600689403773245, 0.000000000000000000000000000000000,
+];
+const WGK: [f64; 8] = [
+    0.022935322010529224963732008058970, 0.063092092629978553290700663189204,
+    0.104790010322250183839876322541518, 0.140653259715525918745189590510238,
+    0.169004726639267902826583426598550, 0.190350578064785409913256402421014,
+    0.204432940075298892414161999234649, 0.209482141084727828012999174891714,
+];
+const WG: [f64; 4] = [
+    0.129484966168869693270611432679082, 0.279705391489276667901467771423780,
+    0.381830050505118944950369775488975, 0.417959183673469387755102040816327,
+];
+const GK_LIMIT: usize = 1000;
+
 pub struct Integral<F: Float + FloatConst> {
     pub rhs: fn(F) -> F,
 }
@@ -13,112 +31,193 @@ impl<F:Float + FloatConst> Integral<F> {
     }
 
     pub fn trapezoid(&self, a: F, b: F, n: usize) -> F {
-        if a == b {
-            return Zero::zero();
-        }
-        let (a, b, reverse) = if a > b {
-            (b, a, true)
-        } else {
-            (a, b, false)
-        };
-        let dh = (b - a) / F::from(n).unwrap();
-        let zero: F = Zero::zero();
-        let one: F = One::one();
-        let two: F = one + one;
-        let s1 = ((self.rhs)(a) + (self.rhs)(b)) / two;
-        let s2 = (1..n).map(|i| (self.rhs)(a + F::from(i).unwrap() * dh)).fold(zero, |m, j| m + j);
-        if reverse {
-            zero - (s1 + s2) * dh
-        } else {
-            (s1 + s2) * dh
-        }
+        trapezoid_rule(self.rhs, a, b, n)
     }
 
     pub fn simpson(&self, a: F, b: F, n: usize) -> F {
-        if a == b {
-            return Zero::zero();
-        }
-        let (a, b, reverse) = if a > b {
-            (b, a, true)
-        } else {
-            (a, b, false)
-        };
-        let n2 = n + n;
-        let dh = (b - a) / F::from(n2).unwrap();
-        let zero: F = Zero::zero();
-        let one: F = One::one();
-        let two: F = one + one;
-        let three: F = two + one;
-        let four: F = two + two;
-        let f: Vec<F> = (0..n2+1).map(|i| (self.rhs)(a + F::from(i).unwrap() * dh)).collect();
-        let s1 = f[0] + f[n2];
-        let s2 = (1..n).map(|i| f[2 * i] * two).fold(zero, |m, j| m + j);
-        let s4 = (0..n).map(|i| f[2 * i + 1] * four).fold(zero, |m, j| m + j);
-        if reverse {
-            zero - (s1 + s2 + s4) * dh / three
-        } else {
-            (s1 + s2 + s4) * dh / three
-        }
+        simpson_rule(self.rhs, a, b, n)
     }
 
     pub fn gauss_legendre(&self, a: F, b: F, n: usize) -> F {
-        let alpha: Vec<F> = solve_pnx(n);
-        let w: Vec<F> = calc_weights(&alpha);
-        let zero: F = Zero::zero();
-        let one: F = One::one();
-        let two: F = one + one;
-        let bma2 = (b - a) / two;
-        let bpa2 = (b + a) / two;
-        let tau = |t: F| bma2 * t + bpa2;
-        let s = w.iter().zip(alpha.iter()).map(|(&wi, &ai)| {
-            let xi = tau(ai);
-            wi * (self.rhs)(xi)
-        }).fold(zero, |m, e| m + e);
-        bma2 * s
+        let (x, w) = legendre_nodes(n);
+        quadrature(self.rhs, a, b, &x, &w)
     }
 
     pub fn chebyshev(&self, a: F, b: F, n: usize) -> F {
-        let n2 = n / 2;
-        let n1f: F = F::from(2*(n+1)).unwrap();
-        let theta: Vec<F> = (0..n+1).map(|k: usize| F::from(2*k+1).unwrap() / n1f * F::PI()).collect();
-        let zeta = zeta_vec(n);
-        let zero: F = Zero::zero();
-        let one: F = One::one();
-        let two: F = one + one;
-        let bma2 = (b - a) / two;
-        let bpa2 = (b + a) / two;
-        let tau = |t: F| bma2 * t + bpa2;
-        let f: Vec<F> = zeta.iter().map(|&z| (self.rhs)(tau(z))).collect();
-        let denom: Vec<F> = (1..n2+1).map(|j| F::from(2*j*2*j-1).unwrap()).collect();
-        let n1_hnk = |k: usize| {
-            let s: F = (1..n2+1).map(|j| (F::from(2 * j).unwrap() * theta[k]).cos() / denom[j-1]).fold(zero, |m, i| m + i);
-            one - two * s
-        };
-        let s = (0..n+1).map(|k| n1_hnk(k) * f[k]).fold(zero, |m, i| m + i);
-        (b - a) / F::from(n + 1).unwrap() * s
+        let (x, w) = chebyshev_nodes(n);
+        quadrature(self.rhs, a, b, &x, &w)
     }
 
     pub fn def(&self, a: F, b: F, n: usize, h: F) -> F {
+        def_rule(self.rhs, a, b, n, h)
+    }
+
+    /// adaptive 7-point Gauss / 15-point Kronrod rule, returns (integral, error estimate)
+    pub fn gauss_kronrod(&self, a: F, b: F, tol: F) -> (F, F) {
         let zero: F = Zero::zero();
-        let one: F = One::one();
-        let two: F = one + one;
-        let nf: F = F::from(n).unwrap();
-        let bma2 = (b - a) / two;
-        let bpa2 = (b + a) / two;
-        let tau = |t: F| bma2 * t + bpa2;
-        let pi: F = F::PI();
-        let phi = |t: F| (pi / two * t.sinh()).tanh();
-        let phip = |t: F| pi * t.cosh() / (one + (pi * t.sinh()).cosh());
-        let tn: F = h * nf;
-        let t0: F = zero - tn;
-        let gtau = |t: F| (self.rhs)(tau(phi(t))) * phip(t);
-        let mut s = (gtau(t0) + gtau(tn)) / two;
-        for i in 1..2*n {
-            let t: F = (F::from(i).unwrap() - nf) * h;
-            s = s + gtau(t);
+        adaptive_gk(|x: F| ((self.rhs)(x), zero), a, b, tol)
+    }
+}
+
+/// composite trapezoidal rule with n intervals
+pub fn trapezoid_rule<F: Float, G: Fn(F) -> F>(f: G, a: F, b: F, n: usize) -> F {
+    if a == b {
+        return Zero::zero();
+    }
+    let (a, b, reverse) = if a > b {
+        (b, a, true)
+    } else {
+        (a, b, false)
+    };
+    let dh = (b - a) / F::from(n).unwrap();
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let s1 = (f(a) + f(b)) / two;
+    let s2 = (1..n).map(|i| f(a + F::from(i).unwrap() * dh)).fold(zero, |m, j| m + j);
+    if reverse {
+        zero - (s1 + s2) * dh
+    } else {
+        (s1 + s2) * dh
+    }
+}
+
+/// composite Simpson's rule with 2n intervals
+pub fn simpson_rule<F: Float, G: Fn(F) -> F>(f: G, a: F, b: F, n: usize) -> F {
+    if a == b {
+        return Zero::zero();
+    }
+    let (a, b, reverse) = if a > b {
+        (b, a, true)
+    } else {
+        (a, b, false)
+    };
+    let n2 = n + n;
+    let dh = (b - a) / F::from(n2).unwrap();
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let three: F = two + one;
+    let four: F = two + two;
+    let fx: Vec<F> = (0..n2+1).map(|i| f(a + F::from(i).unwrap() * dh)).collect();
+    let s1 = fx[0] + fx[n2];
+    let s2 = (1..n).map(|i| fx[2 * i] * two).fold(zero, |m, j| m + j);
+    let s4 = (0..n).map(|i| fx[2 * i + 1] * four).fold(zero, |m, j| m + j);
+    if reverse {
+        zero - (s1 + s2 + s4) * dh / three
+    } else {
+        (s1 + s2 + s4) * dh / three
+    }
+}
+
+/// (b - a) / 2 sum_i w[i] f(x(t[i])) for the nodes t and weights w on [-1, 1]
+pub fn quadrature<F: Float, G: Fn(F) -> F>(f: G, a: F, b: F, t: &Vec<F>, w: &Vec<F>) -> F {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let bma2 = (b - a) / two;
+    let bpa2 = (b + a) / two;
+    let s = w.iter().zip(t.iter()).map(|(&wi, &ti)| wi * f(bma2 * ti + bpa2)).fold(zero, |m, e| m + e);
+    bma2 * s
+}
+
+/// nodes and weights of the n-point Gauss-Legendre rule on [-1, 1]
+pub fn legendre_nodes<F: Float>(n: usize) -> (Vec<F>, Vec<F>) {
+    let t: Vec<F> = solve_pnx(n);
+    let w = calc_weights(&t);
+    (t, w)
+}
+
+/// the n + 1 Chebyshev nodes and the weights of the interpolatory rule on [-1, 1]
+pub fn chebyshev_nodes<F: Float + FloatConst>(n: usize) -> (Vec<F>, Vec<F>) {
+    let n2 = n / 2;
+    let n1f: F = F::from(2*(n+1)).unwrap();
+    let theta: Vec<F> = (0..n+1).map(|k: usize| F::from(2*k+1).unwrap() / n1f * F::PI()).collect();
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let denom: Vec<F> = (1..n2+1).map(|j| F::from(2*j*2*j-1).unwrap()).collect();
+    let w = (0..n+1).map(|k| {
+        let s: F = (1..n2+1).map(|j| (F::from(2 * j).unwrap() * theta[k]).cos() / denom[j-1]).fold(zero, |m, i| m + i);
+        two * (one - two * s) / F::from(n + 1).unwrap()
+    }).collect();
+    (zeta_vec(n), w)
+}
+
+/// double exponential (tanh-sinh) rule with 2n steps of width h
+pub fn def_rule<F: Float + FloatConst, G: Fn(F) -> F>(f: G, a: F, b: F, n: usize, h: F) -> F {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let two: F = one + one;
+    let nf: F = F::from(n).unwrap();
+    let bma2 = (b - a) / two;
+    let bpa2 = (b + a) / two;
+    let tau = |t: F| bma2 * t + bpa2;
+    let pi: F = F::PI();
+    let phi = |t: F| (pi / two * t.sinh()).tanh();
+    let phip = |t: F| pi * t.cosh() / (one + (pi * t.sinh()).cosh());
+    let tn: F = h * nf;
+    let t0: F = zero - tn;
+    let gtau = |t: F| f(tau(phi(t))) * phip(t);
+    let mut s = (gtau(t0) + gtau(tn)) / two;
+    for i in 1..2*n {
+        let t: F = (F::from(i).unwrap() - nf) * h;
+        s = s + gtau(t);
+    }
+    s * h * bma2
+}
+
+/// Adaptive Gauss-Kronrod integration of `f` returning (value, error) at each node.
+/// The interval with the largest quadrature error is bisected until the sum of the
+/// quadrature errors falls below `tol`.
+/// Returns (integral, quadrature error + integrated node errors).
+pub fn adaptive_gk<F: Float, G: Fn(F) -> (F, F)>(f: G, a: F, b: F, tol: F) -> (F, F) {
+    let zero: F = Zero::zero();
+    if a == b {
+        return (zero, zero);
+    }
+    let mut panels = vec![gk15(&f, a, b)];
+    let mut err = panels[0].3;
+    while err > tol && panels.len() < GK_LIMIT {
+        let (i, _) = panels.iter().enumerate().fold((0, zero), |m, (i, p)| if p.3 > m.1 { (i, p.3) } else { m });
+        let (a1, b1, _, _, _) = panels.swap_remove(i);
+        let mid = (a1 + b1) / F::from(2).unwrap();
+        panels.push(gk15(&f, a1, mid));
+        panels.push(gk15(&f, mid, b1));
+        err = panels.iter().fold(zero, |m, p| m + p.3);
+    }
+    let s = panels.iter().fold(zero, |m, p| m + p.2);
+    let e = panels.iter().fold(zero, |m, p| m + p.3 + p.4);
+    (s, e)
+}
+
+/// (a, b, Kronrod value, |Kronrod - Gauss|, integrated node error) on [a, b]
+fn gk15<F: Float, G: Fn(F) -> (F, F)>(f: &G, a: F, b: F) -> (F, F, F, F, F) {
+    let zero: F = Zero::zero();
+    let two = F::from(2).unwrap();
+    let c = (a + b) / two;
+    let h = (b - a) / two;
+    let mut rk = zero;
+    let mut rg = zero;
+    let mut re = zero;
+    for i in 0..8 {
+        let wk = F::from(WGK[i]).unwrap();
+        let nodes = if i == 7 {
+            vec![c]
+        } else {
+            let dx = h * F::from(XGK[i]).unwrap();
+            vec![c - dx, c + dx]
+        };
+        for &x in nodes.iter() {
+            let (v, e) = f(x);
+            rk = rk + wk * v;
+            re = re + wk * e.abs();
+            if i % 2 == 1 {
+                rg = rg + F::from(WG[i / 2]).unwrap() * v;
+            }
         }
-        s * h * bma2
     }
+    (a, b, rk * h, ((rk - rg) * h).abs(), re * h.abs())
 }
 
 pub fn solve_pnx<F: Float>(n: usize) -> Vec<F> {
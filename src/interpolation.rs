@@ -1,42 +1,270 @@
-use num_traits::float::Float;
-use num_traits::{Zero, One};
+use num_traits::float::{Float, FloatConst};
+use num_traits::{Zero, One, NumCast};
+use crate::polynomial::zeta_vec;
+use crate::fit::calc_piecewise;
+use ndarray::{Array, Array2, Dimension};
+use ndarray_linalg::SVD;
+use ndarray_linalg::lapack::Lapack;
+use std::fmt;
 
-pub trait Itp<F: Float> {
-    fn new(x: &Vec<F>, y: &Vec<F>) -> Self;
-    fn push(&mut self, x: F, y: F);
-    fn push_vec(&mut self, x: &Vec<F>, y: &Vec<F>);
-    fn calc(&self, x: F) -> F;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItpError {
+    /// x and y have different number of elements
+    LengthMismatch,
+    /// at least this number of points is required
+    TooFewPoints(usize),
+    /// the point at this index has a non-finite x or y
+    NonFinite(usize),
+    /// the point at this index has the same x as another point
+    DuplicateAbscissa(usize),
+    /// the point at this index breaks the equal spacing of x
+    NotEquispaced(usize),
+    /// only the end points can be removed, not the interior point at this index
+    InteriorRemoval(usize),
+    /// there is no point at this index
+    IndexOutOfRange(usize),
+    /// the period is zero or not finite
+    InvalidPeriod,
 }
 
-pub struct LagrangeItp<F: Float> {
-    pub x: Vec<F>,
-    pub y: Vec<F>,
+impl fmt::Display for ItpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ItpError::LengthMismatch => write!(f, "x and y have different number of elements"),
+            ItpError::TooFewPoints(n) => write!(f, "at least {} points are required", n),
+            ItpError::NonFinite(i) => write!(f, "point {} is not finite", i),
+            ItpError::DuplicateAbscissa(i) => write!(f, "x of point {} is duplicated", i),
+            ItpError::NotEquispaced(i) => write!(f, "x of point {} is not equally spaced", i),
+            ItpError::InteriorRemoval(i) => write!(f, "point {} is not at an end and cannot be removed", i),
+            ItpError::IndexOutOfRange(i) => write!(f, "point {} does not exist", i),
+            ItpError::InvalidPeriod => write!(f, "period should be finite and nonzero"),
+        }
+    }
 }
 
-impl<F: Float> Itp<F> for LagrangeItp<F> {
-    fn new(x: &Vec<F>, y: &Vec<F>) -> Self {
+impl std::error::Error for ItpError {}
+
+/// Evaluation outside the range of the knots
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Extrapolation {
+    /// return Err("out of range!")
+    Error,
+    /// value at the nearest end
+    Clamp,
+    /// tangent line at the nearest end
+    Linear,
+    /// continue the polynomial of the end segment
+    Polynomial,
+    /// wrap into the range with period x[n-1] - x[0]
+    Periodic,
+}
+
+impl Default for Extrapolation {
+    fn default() -> Self {
+        Extrapolation::Error
+    }
+}
+
+/// Interpolation of the points (x, y), evaluated by `calc` with the `Extrapolation` policy
+/// outside the range of x, which is Polynomial (the interpolant itself) after `new`
+pub trait Itp<F: Float>: Sized {
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError>;
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError>;
+    /// remove the point at index i (in the order of `x`) and return it
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError>;
+    fn len(&self) -> usize;
+    /// the interpolant at x, continued outside the range of the points
+    fn interpolate(&self, x: F) -> F;
+    /// (smallest, largest) x, the range outside of which `extrapolation` applies
+    fn range(&self) -> (F, F);
+    fn extrapolation(&self) -> Extrapolation;
+    fn set_extrapolation(&mut self, ext: Extrapolation);
+
+    fn with_extrapolation(mut self, ext: Extrapolation) -> Self {
+        self.set_extrapolation(ext);
+        self
+    }
+
+    /// first derivative of the interpolant by central differences
+    fn slope(&self, x: F) -> F {
+        let one: F = One::one();
+        let h = F::epsilon().cbrt() * x.abs().max(one);
+        (self.interpolate(x + h) - self.interpolate(x - h)) / (h + h)
+    }
+
+    /// value at x with the extrapolation policy, Err("out of range!") for Extrapolation::Error
+    fn try_calc(&self, x: F) -> Result<F, &'static str> {
+        let (lo, hi) = self.range();
+        if x >= lo && x <= hi {
+            return Ok(self.interpolate(x));
+        }
+        calc_piecewise(&vec![lo, hi], self.extrapolation(), x, 0, |_, t, d| {
+            if d == 0 { self.interpolate(lo + t) } else { self.slope(lo + t) }
+        })
+    }
+
+    /// try_calc, NaN out of range for Extrapolation::Error
+    fn calc(&self, x: F) -> F {
+        self.try_calc(x).unwrap_or_else(|_| F::nan())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn pop_front(&mut self) -> Result<(F, F), ItpError> {
+        self.remove(0)
+    }
+
+    fn calc_array<D: Dimension>(&self, x: &Array<F, D>) -> Array<F, D> {
+        x.mapv(|xi| self.calc(xi))
+    }
+
+    /// push points one by one, the points before a rejected one are kept
+    fn push_vec(&mut self, x: &Vec<F>, y: &Vec<F>) -> Result<(), ItpError> {
         if x.len() != y.len() {
-            panic!("x and y have different number of elements!");
+            return Err(ItpError::LengthMismatch);
         }
-        Self { x: x.clone(), y: y.clone() }
+        for (&xi, &yi) in x.iter().zip(y.iter()) {
+            self.push(xi, yi)?;
+        }
+        Ok(())
     }
+}
 
-    fn push(&mut self, x: F, y: F) {
-        self.x.push(x);
-        self.y.push(y);
+/// check lengths, finiteness and distinctness of the abscissae
+pub fn validate<F: Float>(x: &Vec<F>, y: &Vec<F>, min: usize) -> Result<(), ItpError> {
+    let n = x.len();
+    if n != y.len() {
+        return Err(ItpError::LengthMismatch);
+    }
+    if n < min {
+        return Err(ItpError::TooFewPoints(min));
+    }
+    if let Some(i) = (0..n).find(|&i| !x[i].is_finite() || !y[i].is_finite()) {
+        return Err(ItpError::NonFinite(i));
+    }
+    let mut idx: Vec<usize> = (0..n).collect();
+    idx.sort_by(|&i, &j| x[i].partial_cmp(&x[j]).unwrap().then(i.cmp(&j)));
+    match idx.windows(2).find(|w| x[w[0]] == x[w[1]]) {
+        Some(w) => Err(ItpError::DuplicateAbscissa(w[1])),
+        None => Ok(()),
+    }
+}
+
+/// check the removal of the point i out of n, keeping at least min points
+pub fn validate_remove(n: usize, i: usize, min: usize) -> Result<(), ItpError> {
+    if i >= n {
+        return Err(ItpError::IndexOutOfRange(i));
+    }
+    if n <= min {
+        return Err(ItpError::TooFewPoints(min));
+    }
+    Ok(())
+}
+
+/// check a point to be appended to the abscissae `xs`
+pub fn validate_push<F: Float>(xs: &Vec<F>, x: F, y: F) -> Result<(), ItpError> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(ItpError::NonFinite(xs.len()));
+    }
+    if xs.iter().any(|&xi| xi == x) {
+        return Err(ItpError::DuplicateAbscissa(xs.len()));
+    }
+    Ok(())
+}
+
+/// (smallest, largest) element of x
+pub fn bounds<F: Float>(x: &Vec<F>) -> (F, F) {
+    x.iter().fold((F::infinity(), F::neg_infinity()), |(lo, hi), &v| (lo.min(v), hi.max(v)))
+}
+
+/// Interpolation on the last `capacity` points of a stream,
+/// the oldest point is dropped by `pop_front` after each push beyond the capacity
+#[derive(Clone)]
+pub struct SlidingItp<I> {
+    pub itp: I,
+    pub capacity: usize,
+}
+
+impl<I> SlidingItp<I> {
+    /// interpolation on the last `capacity` of the points
+    pub fn new<F: Float>(x: &Vec<F>, y: &Vec<F>, capacity: usize) -> Result<Self, ItpError> where I: Itp<F> {
+        if capacity == 0 {
+            return Err(ItpError::TooFewPoints(1));
+        }
+        if x.len() != y.len() {
+            return Err(ItpError::LengthMismatch);
+        }
+        let skip = x.len().saturating_sub(capacity);
+        let itp = I::new(&x[skip..].to_vec(), &y[skip..].to_vec())?;
+        Ok(Self { itp, capacity })
     }
 
-    fn push_vec(&mut self, x: &Vec<F>, y: &Vec<F>) {
+    /// the window is left unchanged if the point is rejected or the oldest point cannot be dropped
+    pub fn push<F: Float>(&mut self, x: F, y: F) -> Result<(), ItpError> where I: Itp<F> + Clone {
+        let saved = if self.itp.len() >= self.capacity { Some(self.itp.clone()) } else { None };
+        self.itp.push(x, y)?;
+        while self.itp.len() > self.capacity {
+            if let Err(e) = self.itp.pop_front() {
+                if let Some(itp) = saved {
+                    self.itp = itp;
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn push_vec<F: Float>(&mut self, x: &Vec<F>, y: &Vec<F>) -> Result<(), ItpError> where I: Itp<F> + Clone {
         if x.len() != y.len() {
-            panic!("x and y have different number of elements!");
+            return Err(ItpError::LengthMismatch);
         }
         for (&xi, &yi) in x.iter().zip(y.iter()) {
-            self.x.push(xi);
-            self.y.push(yi);
+            self.push(xi, yi)?;
         }
+        Ok(())
+    }
+
+    pub fn calc<F: Float>(&self, z: F) -> F where I: Itp<F> {
+        self.itp.calc(z)
+    }
+
+    pub fn calc_array<F: Float, D: Dimension>(&self, z: &Array<F, D>) -> Array<F, D> where I: Itp<F> {
+        self.itp.calc_array(z)
+    }
+}
+
+#[derive(Clone)]
+pub struct LagrangeItp<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> Itp<F> for LagrangeItp<F> {
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 1)?;
+        Ok(Self { x: x.clone(), y: y.clone(), ext: Extrapolation::Polynomial })
+    }
+
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        self.x.push(x);
+        self.y.push(y);
+        Ok(())
     }
 
-    fn calc(&self, z: F) -> F {
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        validate_remove(self.x.len(), i, 1)?;
+        Ok((self.x.remove(i), self.y.remove(i)))
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
         let mut ans: F = Zero::zero();
         for (i, (&xi, &yi)) in self.x.iter().zip(self.y.iter()).enumerate() {
             let mut l: F = One::one();
@@ -49,34 +277,343 @@ impl<F: Float> Itp<F> for LagrangeItp<F> {
         }
         ans
     }
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+/// Lagrange interpolation in the barycentric (second) form
+#[derive(Clone)]
+pub struct BaryLagrangeItp<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub w: Vec<F>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> Itp<F> for BaryLagrangeItp<F> {
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 1)?;
+        let n = x.len();
+        let one: F = One::one();
+        let (min, max) = x.iter().fold((x[0], x[0]), |m, &xi| (m.0.min(xi), m.1.max(xi)));
+        // scaling the differences by the capacity 4 / (max - min) avoids overflow of the products
+        let scale = if max > min { F::from(4).unwrap() / (max - min) } else { one };
+        let w = (0..n).map(|j| {
+            one / (0..n).filter(|&k| k != j).fold(one, |m, k| m * (x[j] - x[k]) * scale)
+        }).collect();
+        let mut itp = Self { x: x.clone(), y: y.clone(), w, ext: Extrapolation::Polynomial };
+        itp.normalize();
+        Ok(itp)
+    }
+
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        let one: F = One::one();
+        // the new weight is taken relative to w[0] so that any common scaling of w is kept
+        let x0 = self.x[0];
+        let r = self.x.iter().skip(1).fold(one, |m, &xk| m * (x0 - xk) / (x - xk));
+        let wn = self.w[0] / (x - x0) * r;
+        for (wj, &xj) in self.w.iter_mut().zip(self.x.iter()) {
+            *wj = *wj / (xj - x);
+        }
+        self.x.push(x);
+        self.y.push(y);
+        self.w.push(wn);
+        self.normalize();
+        Ok(())
+    }
+
+    /// the weights are downdated by w_j (x_j - x_i)
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        validate_remove(self.x.len(), i, 1)?;
+        let (x, y) = (self.x.remove(i), self.y.remove(i));
+        self.w.remove(i);
+        for (wj, &xj) in self.w.iter_mut().zip(self.x.iter()) {
+            *wj = *wj * (xj - x);
+        }
+        self.normalize();
+        Ok((x, y))
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
+        let zero: F = Zero::zero();
+        let mut numer = zero;
+        let mut denom = zero;
+        for ((&xi, &yi), &wi) in self.x.iter().zip(self.y.iter()).zip(self.w.iter()) {
+            if z == xi {
+                return yi;
+            }
+            let t = wi / (z - xi);
+            numer = numer + t * yi;
+            denom = denom + t;
+        }
+        numer / denom
+    }
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float> BaryLagrangeItp<F> {
+    /// interpolation with precomputed barycentric weights
+    pub fn with_weights(x: &Vec<F>, y: &Vec<F>, w: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 1)?;
+        if x.len() != w.len() {
+            return Err(ItpError::LengthMismatch);
+        }
+        Ok(Self { x: x.clone(), y: y.clone(), w: w.clone(), ext: Extrapolation::Polynomial })
+    }
+
+    /// y sampled at the n+1 equispaced points a + (b - a)j/n, w_j = (-1)^j C(n, j)
+    pub fn equispaced(a: F, b: F, y: &Vec<F>) -> Result<Self, ItpError> {
+        let one: F = One::one();
+        if y.len() < 2 {
+            return Err(ItpError::TooFewPoints(2));
+        }
+        let n = y.len() - 1;
+        let x = (0..n+1).map(|j| a + (b - a) * F::from(j).unwrap() / F::from(n).unwrap()).collect();
+        validate(&x, y, 2)?;
+        let mut w = Vec::with_capacity(n+1);
+        let mut c = one;
+        for j in 0..n+1 {
+            w.push(if j % 2 == 0 { c } else { -c });
+            c = c * F::from(n - j).unwrap() / F::from(j + 1).unwrap();
+        }
+        Ok(Self { x, y: y.clone(), w, ext: Extrapolation::Polynomial })
+    }
+
+    /// the largest |w| is scaled to 1, the interpolant is invariant under this scaling
+    fn normalize(&mut self) {
+        let zero: F = Zero::zero();
+        let max = self.w.iter().fold(zero, |m, &wi| m.max(wi.abs()));
+        if max > zero && max.is_finite() {
+            for wi in self.w.iter_mut() {
+                *wi = *wi / max;
+            }
+        }
+    }
+}
+
+impl<F: Float + FloatConst> BaryLagrangeItp<F> {
+    /// y sampled at the Chebyshev points of `zeta_vec(n)` mapped to [a, b],
+    /// w_j = (-1)^j sin((2j+1)pi / (2n+2))
+    pub fn chebyshev(a: F, b: F, y: &Vec<F>) -> Result<Self, ItpError> {
+        let one: F = One::one();
+        let two: F = one + one;
+        if y.len() < 1 {
+            return Err(ItpError::TooFewPoints(1));
+        }
+        let n = y.len() - 1;
+        let x = zeta_vec(n).iter().map(|&z| (b - a) / two * z + (b + a) / two).collect();
+        validate(&x, y, 1)?;
+        let n1f = F::from(2 * (n + 1)).unwrap();
+        let w = (0..n+1).map(|j| {
+            let s = (F::from(2 * j + 1).unwrap() / n1f * F::PI()).sin();
+            if j % 2 == 0 { s } else { -s }
+        }).collect();
+        Ok(Self { x, y: y.clone(), w, ext: Extrapolation::Polynomial })
+    }
+}
+
+/// Neville-Aitken interpolation on all points or on the k nearest points to the query
+#[derive(Clone)]
+pub struct NevilleItp<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    /// number of nearest points used for each evaluation (None: all points)
+    pub k: Option<usize>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> Itp<F> for NevilleItp<F> {
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 2)?;
+        Ok(Self { x: x.clone(), y: y.clone(), k: None, ext: Extrapolation::Polynomial })
+    }
+
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        self.x.push(x);
+        self.y.push(y);
+        Ok(())
+    }
+
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        validate_remove(self.x.len(), i, 2)?;
+        Ok((self.x.remove(i), self.y.remove(i)))
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
+        self.calc_with_error(z).0
+    }
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float> NevilleItp<F> {
+    /// local interpolation through the k (>= 2) nearest points
+    pub fn with_nearest(x: &Vec<F>, y: &Vec<F>, k: usize) -> Result<Self, ItpError> {
+        if k < 2 {
+            return Err(ItpError::TooFewPoints(2));
+        }
+        validate(x, y, k)?;
+        Ok(Self { x: x.clone(), y: y.clone(), k: Some(k), ext: Extrapolation::Polynomial })
+    }
+
+    /// returns (value, difference between the last two orders of the tableau)
+    pub fn calc_with_error(&self, z: F) -> (F, F) {
+        if z.is_nan() {
+            return (F::nan(), F::nan());
+        }
+        let n = self.x.len();
+        let m = match self.k {
+            Some(k) => k.min(n),
+            None => n,
+        };
+        // nearest points first so that the last correction is the smallest one
+        let mut idx: Vec<usize> = (0..n).collect();
+        idx.sort_by(|&i, &j| (self.x[i] - z).abs().partial_cmp(&(self.x[j] - z).abs()).unwrap());
+        idx.truncate(m);
+        let x: Vec<F> = idx.iter().map(|&i| self.x[i]).collect();
+        let mut p: Vec<F> = idx.iter().map(|&i| self.y[i]).collect();
+        let mut prev = p[0];
+        for d in 1..m {
+            prev = p[0];
+            for i in 0..m-d {
+                p[i] = ((z - x[i+d]) * p[i] + (x[i] - z) * p[i+1]) / (x[i] - x[i+d]);
+            }
+        }
+        (p[0], (p[0] - prev).abs())
+    }
 }
 
+#[derive(Clone)]
 pub struct NewtonDivItp<F: Float> {
     pub x: Vec<F>,
     pub y: Vec<F>,
     pub diff: Vec<Vec<F>>,
+    pub ext: Extrapolation,
 }
 
 impl<F: Float> Itp<F> for NewtonDivItp<F> {
-    fn new(x: &Vec<F>, y: &Vec<F>) -> Self {
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 2)?;
         let n = x.len();
-        if n != y.len() {
-            panic!("x and y have different number of elements!");
-        }
         let x = x.clone();
         let y = y.clone();
         let mut diff: Vec<Vec<F>> = vec![(0..n-1).map(|i| (y[i] - y[i+1]) / (x[i] - x[i+1])).collect()];
         for i in 1..n-1 {
             diff.push((0..n-i-1).map(|j| (diff[i-1][j] - diff[i-1][j+1]) / (x[j] - x[j+i+1])).collect());
         }
-        Self { x, y, diff }
+        Ok(Self { x, y, diff, ext: Extrapolation::Polynomial })
     }
 
-    fn push(&mut self, x: F, y: F) {
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        let n = self.x.len();
+        self.push_point(x, y, (self.y[n-1] - y) / (self.x[n-1] - x));
+        Ok(())
+    }
+
+    /// only the differences whose span contains the removed point are recomputed,
+    /// none of them when it is the first or the last point
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        let n = self.x.len();
+        validate_remove(n, i, 2)?;
+        let removed = (self.x.remove(i), self.y.remove(i));
+        self.diff.pop();
+        // diff[l][j] = f[x_j, ..., x_{j+l+1}], the spans j < i <= j + l + 1 cross the gap
+        for l in 0..n-2 {
+            let lo = i.saturating_sub(l + 1);
+            let hi = i.min(n - 2 - l);
+            let new: Vec<F> = (lo..hi).map(|j| {
+                let d_diff = if l == 0 {
+                    self.y[j] - self.y[j+1]
+                } else {
+                    self.diff[l-1][j] - self.diff[l-1][j+1]
+                };
+                d_diff / (self.x[j] - self.x[j+l+1])
+            }).collect();
+            self.diff[l].splice(lo..hi+1, new);
+        }
+        Ok(removed)
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
+        let mut ans: F = self.y[0];
+        let mut m: F = One::one();
+        for i in 0..self.diff.len() {
+            m = m * (z - self.x[i]);
+            ans = ans + m * self.diff[i][0];
+        }
+        ans
+    }
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float> NewtonDivItp<F> {
+    /// append a point whose first divided difference with the last point is `first`
+    fn push_point(&mut self, x: F, y: F, first: F) {
         let n = self.x.len();
         self.x.push(x);
         self.y.push(y);
-        self.diff[0].push((self.y[n-1] - self.y[n]) / (self.x[n-1] - self.x[n]));
+        if n == 1 {
+            self.diff.push(vec![first]);
+            return;
+        }
+        self.diff[0].push(first);
         for i in 1..n-1 {
             let d_diff = self.diff[i-1][n-1-i] - self.diff[i-1][n-i];
             self.diff[i].push(d_diff / (self.x[n-i-1] - self.x[n]));
@@ -84,65 +621,87 @@ impl<F: Float> Itp<F> for NewtonDivItp<F> {
         let d_diff = self.diff[n-2][0] - self.diff[n-2][1];
         self.diff.push(vec![d_diff / (self.x[0] - self.x[n])]);
     }
+}
 
-    fn push_vec(&mut self, x: &Vec<F>, y: &Vec<F>) {
-        if x.len() != y.len() {
-            panic!("x and y have different number of elements!");
+/// Hermite interpolation on values and first derivatives,
+/// Newton's divided differences on the doubled nodes x0, x0, x1, x1, ...
+#[derive(Clone)]
+pub struct HermiteItp<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub dy: Vec<F>,
+    pub itp: NewtonDivItp<F>,
+}
+
+impl<F: Float> HermiteItp<F> {
+    pub fn new(x: &Vec<F>, y: &Vec<F>, dy: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 1)?;
+        if x.len() != dy.len() {
+            return Err(ItpError::LengthMismatch);
         }
-        for (&xi, &yi) in x.iter().zip(y.iter()) {
-            self.push(xi, yi);
+        if let Some(i) = dy.iter().position(|d| !d.is_finite()) {
+            return Err(ItpError::NonFinite(i));
+        }
+        let mut itp = NewtonDivItp { x: vec![x[0]], y: vec![y[0]], diff: Vec::new(), ext: Extrapolation::Polynomial };
+        itp.push_point(x[0], y[0], dy[0]);
+        let mut hermite = Self { x: vec![x[0]], y: vec![y[0]], dy: vec![dy[0]], itp };
+        for i in 1..x.len() {
+            hermite.push(x[i], y[i], dy[i])?;
         }
+        Ok(hermite)
     }
 
-    fn calc(&self, z: F) -> F {
-        let mut ans: F = self.y[0];
-        let mut m: F = One::one();
-        for i in 0..self.diff.len() {
-            m = m * (z - self.x[i]);
-            ans = ans + m * self.diff[i][0];
+    pub fn push(&mut self, x: F, y: F, dy: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        if !dy.is_finite() {
+            return Err(ItpError::NonFinite(self.x.len()));
         }
-        ans
+        let n = self.x.len();
+        self.itp.push_point(x, y, (self.y[n-1] - y) / (self.x[n-1] - x));
+        self.itp.push_point(x, y, dy);
+        self.x.push(x);
+        self.y.push(y);
+        self.dy.push(dy);
+        Ok(())
+    }
+
+    pub fn push_vec(&mut self, x: &Vec<F>, y: &Vec<F>, dy: &Vec<F>) -> Result<(), ItpError> {
+        if x.len() != y.len() || x.len() != dy.len() {
+            return Err(ItpError::LengthMismatch);
+        }
+        for i in 0..x.len() {
+            self.push(x[i], y[i], dy[i])?;
+        }
+        Ok(())
+    }
+
+    pub fn calc(&self, z: F) -> F {
+        self.itp.calc(z)
     }
 }
 
+#[derive(Clone)]
 pub struct NewtonForItp<F: Float> {
     pub x: Vec<F>,
     pub y: Vec<F>,
     pub diff: Vec<Vec<F>>,
+    /// tolerance of the spacing relative to x[1] - x[0]
+    pub rtol: F,
+    pub ext: Extrapolation,
 }
 
 impl<F: Float> Itp<F> for NewtonForItp<F> {
-    fn new(x: &Vec<F>, y: &Vec<F>) -> Self {
-        let n = x.len();
-        if n != y.len() {
-            panic!("x and y have different number of elements!");
-        }
-        if n < 2 {
-            panic!("length of x should be more than 2!");
-        }
-        let dh = x[1] - x[0];
-        let epsilon = F::from(1e-5f64).unwrap();
-        for i in 1..n-1 {
-            if (x[i+1] - x[i] - dh).abs() > epsilon {
-                panic!("x should be equally spaced!");
-            }
-        }
-        let x = x.clone();
-        let y = y.clone();
-        let mut diff: Vec<Vec<F>> = vec![(0..n-1).map(|i| y[i+1] - y[i]).collect()];
-        for i in 1..n-1 {
-            diff.push((0..n-i-1).map(|j| diff[i-1][j+1] - diff[i-1][j]).collect());
-        }
-        Self { x, y, diff }
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        Self::with_rtol(x, y, F::from(1e-6f64).unwrap())
     }
 
-    fn push(&mut self, x: F, y: F) {
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
         let dh = self.x[1] - self.x[0];
-        let epsilon = F::from(1e-5f64).unwrap();
         let n = self.x.len();
-        if (dh - (x - self.x[n-1])).abs() > epsilon {
-            panic!("x should be equally spaced!");
-        } 
+        if (dh - (x - self.x[n-1])).abs() > self.rtol * dh.abs() {
+            return Err(ItpError::NotEquispaced(n));
+        }
         self.x.push(x);
         self.y.push(y);
         self.diff[0].push(self.y[n] - self.y[n-1]);
@@ -152,18 +711,32 @@ impl<F: Float> Itp<F> for NewtonForItp<F> {
         }
         let d_diff = self.diff[n-2][1] - self.diff[n-2][0];
         self.diff.push(vec![d_diff]);
+        Ok(())
     }
 
-    fn push_vec(&mut self, x: &Vec<F>, y: &Vec<F>) {
-        if x.len() != y.len() {
-            panic!("x and y have different number of elements!");
+    /// only the first and the last points can be removed, the differences are kept
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        let n = self.x.len();
+        validate_remove(n, i, 2)?;
+        if i != 0 && i != n - 1 {
+            return Err(ItpError::InteriorRemoval(i));
         }
-        for (&xi, &yi) in x.iter().zip(y.iter()) {
-            self.push(xi, yi);
+        self.diff.pop();
+        for d in self.diff.iter_mut() {
+            if i == 0 {
+                d.remove(0);
+            } else {
+                d.pop();
+            }
         }
+        Ok((self.x.remove(i), self.y.remove(i)))
     }
 
-    fn calc(&self, z: F) -> F {
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
         let one: F = One::one();
         let mut ans: F = self.y[0];
         let mut c: F = one;
@@ -176,4 +749,566 @@ impl<F: Float> Itp<F> for NewtonForItp<F> {
         }
         ans
     }
-}
\ No newline at end of file
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float> NewtonForItp<F> {
+    /// x[i+1] - x[i] may differ from x[1] - x[0] by rtol * |x[1] - x[0]|
+    pub fn with_rtol(x: &Vec<F>, y: &Vec<F>, rtol: F) -> Result<Self, ItpError> {
+        validate(x, y, 2)?;
+        let n = x.len();
+        let dh = x[1] - x[0];
+        for i in 1..n-1 {
+            if (x[i+1] - x[i] - dh).abs() > rtol * dh.abs() {
+                return Err(ItpError::NotEquispaced(i+1));
+            }
+        }
+        let x = x.clone();
+        let y = y.clone();
+        let mut diff: Vec<Vec<F>> = vec![(0..n-1).map(|i| y[i+1] - y[i]).collect()];
+        for i in 1..n-1 {
+            diff.push((0..n-i-1).map(|j| diff[i-1][j+1] - diff[i-1][j]).collect());
+        }
+        Ok(Self { x, y, diff, rtol, ext: Extrapolation::Polynomial })
+    }
+
+    /// Newton's backward formula anchored at x[n-1]
+    pub fn calc_backward(&self, z: F) -> F {
+        let n = self.x.len();
+        self.series(z, n - 1, n, |k| k as isize - 1, |k| k as isize)
+    }
+
+    /// Gauss's forward formula about the node x[s] <= z, accurate for 0 <= p < 1
+    /// (p = (z - x[s]) / h), with at most `order` differences
+    pub fn gauss_forward(&self, z: F, order: usize) -> F {
+        let s = self.origin(z, F::floor, 0);
+        self.series(z, s, order, gauss_off(true), |k| (k / 2) as isize)
+    }
+
+    /// Gauss's backward formula about the node x[s] >= z, accurate for -1 < p <= 0
+    pub fn gauss_backward(&self, z: F, order: usize) -> F {
+        let s = self.origin(z, F::ceil, 0);
+        self.series(z, s, order, gauss_off(false), |k| ((k + 1) / 2) as isize)
+    }
+
+    /// Stirling's formula, the mean of both Gauss formulas about the nearest node
+    pub fn stirling(&self, z: F, order: usize) -> F {
+        let two: F = F::from(2).unwrap();
+        let s = self.origin(z, F::round, 0);
+        let forward = self.series(z, s, order, gauss_off(true), |k| (k / 2) as isize);
+        let backward = self.series(z, s, order, gauss_off(false), |k| ((k + 1) / 2) as isize);
+        (forward + backward) / two
+    }
+
+    /// Bessel's formula, the mean of Gauss's forward formula about x[s] and the backward one
+    /// about x[s+1] (x[s] <= z <= x[s+1]), accurate for p near 1/2
+    pub fn bessel(&self, z: F, order: usize) -> F {
+        let two: F = F::from(2).unwrap();
+        let s = self.origin(z, F::floor, 1);
+        let forward = self.series(z, s, order, gauss_off(true), |k| (k / 2) as isize);
+        let backward = self.series(z, s + 1, order, gauss_off(false), |k| ((k + 1) / 2) as isize);
+        (forward + backward) / two
+    }
+
+    /// formula centered on z with at most `order` differences: Newton's forward or backward
+    /// one near the ends of the table, Bessel's for 1/4 <= p <= 3/4, otherwise Stirling's
+    pub fn calc_auto(&self, z: F, order: usize) -> F {
+        let n = self.x.len();
+        let half = (order + 1) / 2;
+        let p = (z - self.x[0]) / (self.x[1] - self.x[0]);
+        let s = self.origin(z, F::round, 0);
+        if s < half {
+            self.series(z, 0, order, |k| 1 - k as isize, |_| 0)
+        } else if s + half > n - 1 {
+            self.series(z, n - 1, order, |k| k as isize - 1, |k| k as isize)
+        } else {
+            let frac = p - p.floor();
+            if frac >= F::from(0.25).unwrap() && frac <= F::from(0.75).unwrap() {
+                self.bessel(z, order)
+            } else {
+                self.stirling(z, order)
+            }
+        }
+    }
+
+    /// node index round((z - x[0]) / h) clamped to [0, n - 1 - margin]
+    fn origin(&self, z: F, round: fn(F) -> F, margin: usize) -> usize {
+        let n = self.x.len();
+        let p = round((z - self.x[0]) / (self.x[1] - self.x[0]));
+        let max = n.saturating_sub(1 + margin);
+        if !(p >= F::zero()) {
+            0
+        } else {
+            p.to_usize().unwrap_or(max).min(max)
+        }
+    }
+
+    /// y[s] + sum_k prod_{i=1}^{k} (p + off(i)) / k! * diff^k y[s - shift(k)] (p = (z - x[s]) / h),
+    /// up to `order` terms or while the table holds the differences
+    fn series<G: Fn(usize) -> isize, H: Fn(usize) -> isize>(&self, z: F, s: usize, order: usize, off: G, shift: H) -> F {
+        let one: F = One::one();
+        let n = self.x.len() as isize;
+        let p = (z - self.x[s]) / (self.x[1] - self.x[0]);
+        let mut ans = self.y[s];
+        let mut c = one;
+        for k in 1..order+1 {
+            let j = s as isize - shift(k);
+            if j < 0 || j + k as isize > n - 1 {
+                break;
+            }
+            c = c * (p + F::from(off(k)).unwrap()) / F::from(k).unwrap();
+            ans = ans + c * self.diff[k-1][j as usize];
+        }
+        ans
+    }
+}
+
+/// offsets p, p - 1, p + 1, p - 2, ... of Gauss's forward formula, p, p + 1, p - 1, ... of the backward one
+fn gauss_off(forward: bool) -> impl Fn(usize) -> isize {
+    move |k| {
+        let (odd, even) = ((k as isize - 1) / 2, k as isize / 2);
+        match (forward, k % 2 == 1) {
+            (true, true) => odd,
+            (true, false) => -even,
+            (false, true) => -odd,
+            (false, false) => even,
+        }
+    }
+}
+
+/// Floater-Hormann barycentric rational interpolation with blending parameter d,
+/// no real poles and O(h^(d+1)) convergence; x is kept sorted
+#[derive(Clone)]
+pub struct FloaterHormannItp<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub w: Vec<F>,
+    pub d: usize,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float> Itp<F> for FloaterHormannItp<F> {
+    /// d = min(3, n - 1)
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        let d = if x.len() > 3 { 3 } else { x.len().saturating_sub(1) };
+        Self::with_d(x, y, d)
+    }
+
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        let i = self.x.iter().position(|&xi| xi > x).unwrap_or(self.x.len());
+        self.x.insert(i, x);
+        self.y.insert(i, y);
+        self.w = fh_weights(&self.x, self.d);
+        Ok(())
+    }
+
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        validate_remove(self.x.len(), i, self.d + 1)?;
+        let removed = (self.x.remove(i), self.y.remove(i));
+        self.w = fh_weights(&self.x, self.d);
+        Ok(removed)
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
+        let zero: F = Zero::zero();
+        let mut numer = zero;
+        let mut denom = zero;
+        for ((&xi, &yi), &wi) in self.x.iter().zip(self.y.iter()).zip(self.w.iter()) {
+            if z == xi {
+                return yi;
+            }
+            let t = wi / (z - xi);
+            numer = numer + t * yi;
+            denom = denom + t;
+        }
+        numer / denom
+    }
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float> FloaterHormannItp<F> {
+    /// d = 0 gives Berrut's interpolant, d = n - 1 the interpolating polynomial
+    pub fn with_d(x: &Vec<F>, y: &Vec<F>, d: usize) -> Result<Self, ItpError> {
+        validate(x, y, d + 1)?;
+        let mut idx: Vec<usize> = (0..x.len()).collect();
+        idx.sort_by(|&i, &j| x[i].partial_cmp(&x[j]).unwrap());
+        let x: Vec<F> = idx.iter().map(|&i| x[i]).collect();
+        let y: Vec<F> = idx.iter().map(|&i| y[i]).collect();
+        let w = fh_weights(&x, d);
+        Ok(Self { x, y, w, d, ext: Extrapolation::Polynomial })
+    }
+}
+
+/// w_k = sum_{i in J_k} (-1)^i prod_{j=i, j!=k}^{i+d} 1 / (x_k - x_j), J_k = [max(k-d, 0), min(k, n-1-d)]
+fn fh_weights<F: Float>(x: &Vec<F>, d: usize) -> Vec<F> {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let n = x.len();
+    (0..n).map(|k| {
+        (k.saturating_sub(d)..k.min(n - 1 - d)+1).fold(zero, |s, i| {
+            let p = (i..i+d+1).filter(|&j| j != k).fold(one, |m, j| m / (x[k] - x[j]));
+            if i % 2 == 0 { s + p } else { s - p }
+        })
+    }).collect()
+}
+
+/// AAA (adaptive Antoulas-Anderson) rational approximation of the samples (x, y):
+/// support points are added greedily at the worst sample until the maximum error
+/// is below tol * max|y|, the barycentric weights minimize the linearized least-squares
+/// error through the SVD of the Loewner matrix
+#[derive(Clone)]
+pub struct AaaItp<F: Float + Lapack> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub tol: F,
+    pub mmax: usize,
+    /// support points, values and weights
+    pub z: Vec<F>,
+    pub f: Vec<F>,
+    pub w: Vec<F>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float + Lapack> Itp<F> for AaaItp<F> {
+    /// tol = 1e-13, at most 100 support points
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        Self::with_tol(x, y, <F as NumCast>::from(1e-13).unwrap(), 100)
+    }
+
+    /// the approximation is recomputed from all the samples
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        validate_push(&self.x, x, y)?;
+        self.x.push(x);
+        self.y.push(y);
+        self.fit();
+        Ok(())
+    }
+
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        validate_remove(self.x.len(), i, 2)?;
+        let removed = (self.x.remove(i), self.y.remove(i));
+        self.fit();
+        Ok(removed)
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn interpolate(&self, x: F) -> F {
+        let zero: F = Zero::zero();
+        let mut numer = zero;
+        let mut denom = zero;
+        for ((&zi, &fi), &wi) in self.z.iter().zip(self.f.iter()).zip(self.w.iter()) {
+            if x == zi {
+                return fi;
+            }
+            let t = wi / (x - zi);
+            numer = numer + t * fi;
+            denom = denom + t;
+        }
+        numer / denom
+    }
+
+    fn range(&self) -> (F, F) {
+        bounds(&self.x)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float + Lapack> AaaItp<F> {
+    pub fn with_tol(x: &Vec<F>, y: &Vec<F>, tol: F, mmax: usize) -> Result<Self, ItpError> {
+        validate(x, y, 2)?;
+        let mut itp = Self { x: x.clone(), y: y.clone(), tol, mmax, z: Vec::new(), f: Vec::new(), w: Vec::new(), ext: Extrapolation::Polynomial };
+        itp.fit();
+        Ok(itp)
+    }
+
+    fn fit(&mut self) {
+        let zero: F = Zero::zero();
+        let n = self.x.len();
+        let (x, y) = (&self.x, &self.y);
+        let scale = y.iter().fold(zero, |m, &yi| m.max(Float::abs(yi)));
+        let mean = y.iter().fold(zero, |m, &yi| m + yi) / <F as NumCast>::from(n).unwrap();
+        let mut r = vec![mean; n];
+        let mut free = vec![true; n];
+        let (mut z, mut f, mut w): (Vec<F>, Vec<F>, Vec<F>) = (Vec::new(), Vec::new(), Vec::new());
+        // one sample is always left out of the support so that the Loewner matrix is not empty
+        for _ in 0..self.mmax.min(n - 1).max(1) {
+            let j = (0..n).filter(|&i| free[i])
+                .fold(None, |m: Option<usize>, i| match m {
+                    Some(k) if Float::abs(y[k] - r[k]) >= Float::abs(y[i] - r[i]) => Some(k),
+                    _ => Some(i),
+                }).unwrap();
+            free[j] = false;
+            z.push(x[j]);
+            f.push(y[j]);
+            let rows: Vec<usize> = (0..n).filter(|&i| free[i]).collect();
+            let m = z.len();
+            let mut a: Array2<F> = Array2::zeros((rows.len(), m));
+            for (ii, &i) in rows.iter().enumerate() {
+                for k in 0..m {
+                    a[[ii, k]] = (y[i] - f[k]) / (x[i] - z[k]);
+                }
+            }
+            let (_, _, vt) = a.svd(false, true).unwrap();
+            w = vt.unwrap().row(m - 1).to_vec();
+            let mut err = zero;
+            for &i in rows.iter() {
+                let (numer, denom) = (0..m).fold((zero, zero), |(p, q), k| {
+                    let t = w[k] / (x[i] - z[k]);
+                    (p + t * f[k], q + t)
+                });
+                r[i] = numer / denom;
+                err = err.max(Float::abs(y[i] - r[i]));
+            }
+            r[j] = y[j];
+            if err <= self.tol * scale {
+                break;
+            }
+        }
+        self.z = z;
+        self.f = f;
+        self.w = w;
+    }
+}
+
+/// Trigonometric interpolation of the periodic samples y[j] = f(x0 + j period / n),
+/// p(x) = sum_{k <= n/2} a[k] cos(k w (x - x0)) + b[k] sin(k w (x - x0)) with w = 2 pi / period,
+/// the coefficients are computed by `fft`
+#[derive(Clone)]
+pub struct TrigItp<F: Float + FloatConst> {
+    pub x0: F,
+    pub period: F,
+    pub y: Vec<F>,
+    pub a: Vec<F>,
+    pub b: Vec<F>,
+    pub ext: Extrapolation,
+}
+
+impl<F: Float + FloatConst> Itp<F> for TrigItp<F> {
+    /// x should be equispaced within 1e-6 (x[1] - x[0]), the period is n (x[1] - x[0])
+    fn new(x: &Vec<F>, y: &Vec<F>) -> Result<Self, ItpError> {
+        validate(x, y, 2)?;
+        let n = x.len();
+        let dh = x[1] - x[0];
+        let rtol = F::from(1e-6f64).unwrap();
+        for i in 1..n-1 {
+            if (x[i+1] - x[i] - dh).abs() > rtol * dh.abs() {
+                return Err(ItpError::NotEquispaced(i+1));
+            }
+        }
+        Self::with_period(x[0], dh * F::from(n).unwrap(), y)
+    }
+
+    /// x should be the next sample x0 + period, the period grows by one spacing
+    fn push(&mut self, x: F, y: F) -> Result<(), ItpError> {
+        let n = self.y.len();
+        if !x.is_finite() || !y.is_finite() {
+            return Err(ItpError::NonFinite(n));
+        }
+        let dh = self.period / F::from(n).unwrap();
+        if (x - self.x0 - self.period).abs() > F::from(1e-6f64).unwrap() * dh.abs() {
+            return Err(ItpError::NotEquispaced(n));
+        }
+        self.y.push(y);
+        self.period = self.period + dh;
+        self.fit();
+        Ok(())
+    }
+
+    /// only the first and the last samples can be removed, the period shrinks by one spacing
+    fn remove(&mut self, i: usize) -> Result<(F, F), ItpError> {
+        let n = self.y.len();
+        validate_remove(n, i, 2)?;
+        if i != 0 && i != n - 1 {
+            return Err(ItpError::InteriorRemoval(i));
+        }
+        let dh = self.period / F::from(n).unwrap();
+        let x = self.x0 + dh * F::from(i).unwrap();
+        if i == 0 {
+            self.x0 = self.x0 + dh;
+        }
+        self.period = self.period - dh;
+        let y = self.y.remove(i);
+        self.fit();
+        Ok((x, y))
+    }
+
+    fn len(&self) -> usize {
+        self.y.len()
+    }
+
+    fn interpolate(&self, z: F) -> F {
+        self.derivative(z, 0)
+    }
+
+    fn range(&self) -> (F, F) {
+        let x1 = self.x0 + self.period;
+        (self.x0.min(x1), self.x0.max(x1))
+    }
+
+    fn slope(&self, x: F) -> F {
+        self.derivative(x, 1)
+    }
+
+    fn extrapolation(&self) -> Extrapolation {
+        self.ext
+    }
+
+    fn set_extrapolation(&mut self, ext: Extrapolation) {
+        self.ext = ext;
+    }
+}
+
+impl<F: Float + FloatConst> TrigItp<F> {
+    /// samples y[j] at x0 + j period / n
+    pub fn with_period(x0: F, period: F, y: &Vec<F>) -> Result<Self, ItpError> {
+        if y.is_empty() {
+            return Err(ItpError::TooFewPoints(1));
+        }
+        if let Some(i) = y.iter().position(|v| !v.is_finite()) {
+            return Err(ItpError::NonFinite(i));
+        }
+        if !x0.is_finite() {
+            return Err(ItpError::NonFinite(0));
+        }
+        if !(period.is_finite() && period != Zero::zero()) {
+            return Err(ItpError::InvalidPeriod);
+        }
+        let mut itp = Self { x0, period, y: y.clone(), a: Vec::new(), b: Vec::new(), ext: Extrapolation::Polynomial };
+        itp.fit();
+        Ok(itp)
+    }
+
+    /// derivative of the given order (0 for the value)
+    pub fn derivative(&self, z: F, order: usize) -> F {
+        let zero: F = Zero::zero();
+        let w = (F::PI() + F::PI()) / self.period;
+        let t = w * (z - self.x0);
+        let (s1, c1) = t.sin_cos();
+        let (mut s, mut c) = (zero, One::one());
+        let mut ans = zero;
+        for k in 0..self.a.len() {
+            // d/dt (a cos + b sin) = b cos - a sin
+            let (a, b) = match order % 4 {
+                0 => (self.a[k], self.b[k]),
+                1 => (self.b[k], -self.a[k]),
+                2 => (-self.a[k], -self.b[k]),
+                _ => (-self.b[k], self.a[k]),
+            };
+            let scale = (F::from(k).unwrap() * w).powi(order as i32);
+            ans = ans + scale * (a * c + b * s);
+            let (sn, cn) = (s * c1 + c * s1, c * c1 - s * s1);
+            s = sn;
+            c = cn;
+        }
+        ans
+    }
+
+    /// values of the interpolant at the m points x0 + j period / m, by folding
+    /// the spectrum onto m bins and an inverse FFT
+    pub fn resample(&self, m: usize) -> Result<Vec<F>, ItpError> {
+        if m == 0 {
+            return Err(ItpError::TooFewPoints(1));
+        }
+        let zero: F = Zero::zero();
+        let two: F = F::from(2).unwrap();
+        let n = self.y.len();
+        let mut bins = vec![(zero, zero); m];
+        let mut add = |k: isize, c: (F, F)| {
+            let l = k.rem_euclid(m as isize) as usize;
+            bins[l] = (bins[l].0 + c.0, bins[l].1 + c.1);
+        };
+        add(0, (self.a[0], zero));
+        for k in 1..self.a.len() {
+            if 2 * k == n {
+                // a cos(kt) = a / 2 (e^ikt + e^-ikt)
+                add(k as isize, (self.a[k] / two, zero));
+                add(-(k as isize), (self.a[k] / two, zero));
+            } else {
+                add(k as isize, (self.a[k] / two, -self.b[k] / two));
+                add(-(k as isize), (self.a[k] / two, self.b[k] / two));
+            }
+        }
+        Ok(fft(&bins, true).into_iter().map(|v| v.0).collect())
+    }
+
+    fn fit(&mut self) {
+        let zero: F = Zero::zero();
+        let n = self.y.len();
+        let nf = F::from(n).unwrap();
+        let spec = fft(&self.y.iter().map(|&v| (v, zero)).collect(), false);
+        self.a = Vec::with_capacity(n / 2 + 1);
+        self.b = Vec::with_capacity(n / 2 + 1);
+        for (k, &(re, im)) in spec.iter().enumerate().take(n / 2 + 1) {
+            if k == 0 || 2 * k == n {
+                self.a.push(re / nf);
+                self.b.push(zero);
+            } else {
+                self.a.push((re + re) / nf);
+                self.b.push(-(im + im) / nf);
+            }
+        }
+    }
+}
+
+/// discrete Fourier transform X[k] = sum_j x[j] exp(-2 pi i jk / n) of (re, im) pairs,
+/// with +2 pi i and no normalization if inverse, by mixed-radix Cooley-Tukey
+/// in O(n (p_1 + p_2 + ...)) for n = p_1 p_2 ...
+pub fn fft<F: Float + FloatConst>(x: &Vec<(F, F)>, inverse: bool) -> Vec<(F, F)> {
+    let zero: F = Zero::zero();
+    let n = x.len();
+    if n <= 1 {
+        return x.clone();
+    }
+    let p = (2..).take_while(|&p| p * p <= n).find(|&p| n % p == 0).unwrap_or(n);
+    let q = n / p;
+    let sub: Vec<Vec<(F, F)>> = (0..p).map(|r| fft(&(0..q).map(|j| x[j * p + r]).collect(), inverse)).collect();
+    let sign = if inverse { F::one() } else { -F::one() };
+    let tw: Vec<(F, F)> = (0..n).map(|k| {
+        let (s, c) = (sign * (F::PI() + F::PI()) * F::from(k).unwrap() / F::from(n).unwrap()).sin_cos();
+        (c, s)
+    }).collect();
+    (0..n).map(|k| {
+        (0..p).fold((zero, zero), |m, r| {
+            let (c, s) = tw[(r * k) % n];
+            let (u, v) = sub[r][k % q];
+            (m.0 + c * u - s * v, m.1 + c * v + s * u)
+        })
+    }).collect()
+}
@@ -2,8 +2,15 @@ pub mod equation;
 pub mod interpolation;
 pub mod integral;
 pub mod fit;
+pub mod nonlinear;
+pub mod bspline;
+pub mod grid;
+pub mod scattered;
+pub mod stats;
 pub mod polynomial;
 pub mod double;
+pub mod triple;
+pub mod simplex;
 pub mod pde;
 pub mod difference;
 pub mod fem;
\ No newline at end of file
@@ -0,0 +1,230 @@
+use num_traits::float::{Float, FloatConst};
+use num_traits::{Zero, One};
+use ndarray::*;
+use ndarray_linalg::*;
+use ndarray_linalg::lapack::Lapack;
+use crate::fit::{covariance, FitStats};
+
+/// model(x, params)
+pub type Model<F> = fn(F, &Vec<F>) -> F;
+/// gradient of the model with respect to params at x
+pub type Gradient<F> = fn(F, &Vec<F>) -> Vec<F>;
+
+/// Levenberg-Marquardt solver for the weighted nonlinear least squares
+/// min sum_i w[i] (y[i] - model(x[i], params))^2 within optional bounds on the parameters
+pub struct LevenbergMarquardt<F: Float> {
+    pub model: Model<F>,
+    /// gradient of the model with respect to the parameters, forward differences if None
+    pub jacobian: Option<Gradient<F>>,
+    /// (lower, upper) bound of each parameter, no bounds if empty;
+    /// the trial steps are projected onto the box
+    pub bounds: Vec<(F, F)>,
+    /// second order correction of the steps along the geodesic (Transtrum and Sethna)
+    pub geodesic: bool,
+    pub max_iter: usize,
+    /// relative tolerance of the step
+    pub xtol: F,
+    /// tolerance of the largest component of the gradient J^T W r
+    pub gtol: F,
+    /// relative tolerance of the decrease of the weighted RSS
+    pub ftol: F,
+}
+
+/// Result of `LevenbergMarquardt::fit`
+pub struct NonlinearFit<F: Float> {
+    pub x: Vec<F>,
+    pub y: Vec<F>,
+    pub w: Vec<F>,
+    pub model: Model<F>,
+    pub jacobian: Option<Gradient<F>>,
+    pub params: Vec<F>,
+    pub iterations: usize,
+    /// false if max_iter was reached before any tolerance
+    pub converged: bool,
+    /// statistics with the covariance from the Jacobian at the solution
+    pub stats: FitStats<F>,
+}
+
+impl<F: Float + Lapack> LevenbergMarquardt<F> {
+    /// finite-difference Jacobian, no bounds, no geodesic acceleration,
+    /// at most 200 iterations and tolerances of 1e-10
+    pub fn new(model: Model<F>) -> Self {
+        let tol = F::from(1e-10).unwrap();
+        Self { model, jacobian: None, bounds: Vec::new(), geodesic: false, max_iter: 200, xtol: tol, gtol: tol, ftol: tol }
+    }
+
+    pub fn fit(&self, x: &Vec<F>, y: &Vec<F>, w: &Vec<F>, p0: &Vec<F>) -> NonlinearFit<F> {
+        let zero: F = Zero::zero();
+        let one: F = One::one();
+        let two: F = one + one;
+        let n = x.len();
+        let m = p0.len();
+        if n != y.len() || n != w.len() {
+            panic!("x, y and w have different number of elements!");
+        }
+        if m == 0 {
+            panic!("p0 should contain at least 1 parameter!");
+        }
+        if w.iter().any(|&wi| !(wi > zero && wi.is_finite())) {
+            panic!("weights should be positive!");
+        }
+        if !self.bounds.is_empty() && self.bounds.len() != m {
+            panic!("bounds should have p0.len() elements!");
+        }
+        let sw: Vec<F> = w.iter().map(|&wi| Float::sqrt(wi)).collect();
+        // weighted residuals y - f
+        let resid = |p: &Vec<F>| -> Array1<F> {
+            Array1::from_shape_fn(n, |i| sw[i] * (y[i] - (self.model)(x[i], p)))
+        };
+        let cost = |r: &Array1<F>| r.dot(r);
+        let mut p = self.project(p0);
+        let mut r = resid(&p);
+        let mut c = cost(&r);
+        let mut jac = self.weighted_jacobian(x, &sw, &p);
+        let mut d: Vec<F> = (0..m).map(|j| jac.column(j).dot(&jac.column(j))).collect();
+        let mut mu = F::from(1e-3).unwrap() * d.iter().fold(zero, |a, &b| a.max(b));
+        let mut nu = two;
+        let mut converged = false;
+        let mut iterations = 0;
+        while iterations < self.max_iter {
+            iterations += 1;
+            let g = jac.t().dot(&r);
+            if g.iter().fold(zero, |a, &b| a.max(Float::abs(b))) <= self.gtol {
+                converged = true;
+                break;
+            }
+            // Marquardt scaling by the largest squared column norms so far
+            for (j, dj) in d.iter_mut().enumerate() {
+                *dj = dj.max(jac.column(j).dot(&jac.column(j))).max(F::epsilon());
+            }
+            let scale: Vec<F> = d.iter().map(|&dj| Float::sqrt(mu * dj)).collect();
+            let mut a: Array2<F> = Array2::zeros((n + m, m));
+            a.slice_mut(s![..n, ..]).assign(&jac);
+            for j in 0..m {
+                a[[n + j, j]] = scale[j];
+            }
+            let mut b: Array1<F> = Array1::zeros(n + m);
+            b.slice_mut(s![..n]).assign(&r);
+            let v = a.least_squares(&b).unwrap().solution;
+            let mut step = v.clone();
+            if self.geodesic {
+                // second directional derivative of the residual f - y along v
+                let h = F::from(0.1).unwrap();
+                let ph: Vec<F> = (0..m).map(|j| p[j] + h * v[j]).collect();
+                let jv = jac.dot(&v);
+                let rh = resid(&ph);
+                let rvv = Array1::from_shape_fn(n, |i| two / h * ((r[i] - rh[i]) / h - jv[i]));
+                b.slice_mut(s![..n]).assign(&rvv.mapv(|e| -e));
+                let acc = a.least_squares(&b).unwrap().solution;
+                if two * norm(&acc) <= F::from(0.75).unwrap() * norm(&v) {
+                    step = &v + &acc.mapv(|e| e / two);
+                }
+            }
+            let pn = self.project(&(0..m).map(|j| p[j] + step[j]).collect());
+            let dp = Array1::from_shape_fn(m, |j| pn[j] - p[j]);
+            let rn = resid(&pn);
+            let cn = cost(&rn);
+            // decrease predicted by the linear model for the projected step
+            let pr = &r - &jac.dot(&dp);
+            let predicted = c - cost(&pr);
+            let rho = if predicted > zero { (c - cn) / predicted } else { -one };
+            if rho > zero {
+                let small_step = norm(&dp) <= self.xtol * (norm(&Array1::from(p.clone())) + self.xtol);
+                let small_decrease = c - cn <= self.ftol * c;
+                p = pn;
+                r = rn;
+                c = cn;
+                jac = self.weighted_jacobian(x, &sw, &p);
+                let t = two * rho - one;
+                mu = mu * (one / (one + two)).max(one - t * t * t);
+                nu = two;
+                if small_step || small_decrease {
+                    converged = true;
+                    break;
+                }
+            } else {
+                if norm(&dp) <= self.xtol * (norm(&Array1::from(p.clone())) + self.xtol) {
+                    converged = true;
+                    break;
+                }
+                mu = mu * nu;
+                nu = nu + nu;
+            }
+        }
+        let fitted = x.iter().map(|&xi| (self.model)(xi, &p)).collect();
+        let stats = FitStats::new(y, &fitted, w, covariance(&jac, &Array2::zeros((0, m)), zero, None), F::from(m).unwrap());
+        NonlinearFit {
+            x: x.clone(), y: y.clone(), w: w.clone(), model: self.model, jacobian: self.jacobian,
+            params: p, iterations, converged, stats,
+        }
+    }
+
+    fn project(&self, p: &Vec<F>) -> Vec<F> {
+        if self.bounds.is_empty() {
+            return p.clone();
+        }
+        p.iter().zip(self.bounds.iter()).map(|(&pj, &(lo, hi))| pj.max(lo).min(hi)).collect()
+    }
+
+    /// sqrt(w[i]) d model(x[i], p) / d p[j]
+    fn weighted_jacobian(&self, x: &Vec<F>, sw: &Vec<F>, p: &Vec<F>) -> Array2<F> {
+        let n = x.len();
+        let m = p.len();
+        let mut jac: Array2<F> = Array2::zeros((n, m));
+        for i in 0..n {
+            let g = gradient(self.model, self.jacobian, x[i], p, &self.bounds);
+            for j in 0..m {
+                jac[[i, j]] = sw[i] * g[j];
+            }
+        }
+        jac
+    }
+}
+
+impl<F: Float> NonlinearFit<F> {
+    pub fn calc(&self, z: F) -> F {
+        (self.model)(z, &self.params)
+    }
+
+    pub fn calc_array<D: Dimension>(&self, z: &Array<F, D>) -> Array<F, D> {
+        z.mapv(|zi| self.calc(zi))
+    }
+}
+
+impl<F: Float + FloatConst> NonlinearFit<F> {
+    /// confidence interval of the fitted curve at z (linearized in the parameters), e.g. level = 0.95
+    pub fn confidence_interval(&self, z: F, level: F) -> Result<(F, F), &'static str> {
+        let grad = gradient(self.model, self.jacobian, z, &self.params, &Vec::new());
+        self.stats.interval(self.calc(z), &grad, level, false)
+    }
+
+    /// prediction interval of a new observation at z with unit weight
+    pub fn prediction_interval(&self, z: F, level: F) -> Result<(F, F), &'static str> {
+        let grad = gradient(self.model, self.jacobian, z, &self.params, &Vec::new());
+        self.stats.interval(self.calc(z), &grad, level, true)
+    }
+}
+
+/// analytic gradient or forward differences, stepping backwards at an upper bound
+fn gradient<F: Float>(model: Model<F>, jacobian: Option<Gradient<F>>, x: F, p: &Vec<F>, bounds: &Vec<(F, F)>) -> Vec<F> {
+    if let Some(jac) = jacobian {
+        return jac(x, p);
+    }
+    let one: F = One::one();
+    let f0 = model(x, p);
+    let mut q = p.clone();
+    (0..p.len()).map(|j| {
+        let mut h = F::epsilon().sqrt() * p[j].abs().max(one);
+        if !bounds.is_empty() && p[j] + h > bounds[j].1 {
+            h = -h;
+        }
+        q[j] = p[j] + h;
+        let d = (model(x, &q) - f0) / h;
+        q[j] = p[j];
+        d
+    }).collect()
+}
+
+fn norm<F: Float + Lapack>(v: &Array1<F>) -> F {
+    Float::sqrt(v.dot(v))
+}
@@ -1,5 +1,6 @@
 use num_traits::float::{Float, FloatConst};
 use num_traits::{Zero, One};
+use ndarray::{Array, Dimension};
 
 pub struct Chebyshev<F: Float + FloatConst> {
     pub rhs: fn(F) -> F,
@@ -30,6 +31,20 @@ impl<F: Float + FloatConst> Chebyshev<F> {
         let tnx: Vec<F> = tnx_vec(n, x);
         Ok(self.c.iter().zip(tnx.iter()).map(|(&ci, &tix)| ci * tix).fold(zero, |m, i| m + i))
     }
+
+    /// Clenshaw recurrence at every element of x
+    pub fn calc_array<D: Dimension>(&self, x: &Array<F, D>) -> Result<Array<F, D>, &str> {
+        let one: F = One::one();
+        if x.iter().any(|xi| !(xi.abs() <= one)) {
+            return Err("x should be in [-1, 1]");
+        }
+        let zero: F = Zero::zero();
+        let two: F = one + one;
+        Ok(x.mapv(|xi| {
+            let (b1, b2) = self.c.iter().skip(1).rev().fold((zero, zero), |(b1, b2), &ci| (two * xi * b1 - b2 + ci, b1));
+            self.c[0] + xi * b1 - b2
+        }))
+    }
 }
 
 pub fn tnx_vec<F: Float + FloatConst>(n: usize, x: F) -> Vec<F> {
@@ -0,0 +1,337 @@
+use num_traits::float::Float;
+use num_traits::{Zero, One, NumCast};
+use ndarray::*;
+use ndarray_linalg::{Solve, SVD};
+use ndarray_linalg::error::LinalgError;
+use ndarray_linalg::lapack::Lapack;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RbfKernel<F: Float> {
+    /// exp(-(e r)^2) with the shape parameter e
+    Gaussian(F),
+    /// sqrt(1 + (e r)^2)
+    Multiquadric(F),
+    /// 1 / sqrt(1 + (e r)^2)
+    InverseMultiquadric(F),
+    /// r^2 ln(r)
+    ThinPlate,
+    /// r^k for odd k, r^k ln(r) for even k
+    Polyharmonic(usize),
+}
+
+impl<F: Float> RbfKernel<F> {
+    pub fn calc(&self, r: F) -> F {
+        let zero: F = Zero::zero();
+        let one: F = One::one();
+        match *self {
+            RbfKernel::Gaussian(e) => (-(e * r) * (e * r)).exp(),
+            RbfKernel::Multiquadric(e) => (one + (e * r) * (e * r)).sqrt(),
+            RbfKernel::InverseMultiquadric(e) => one / (one + (e * r) * (e * r)).sqrt(),
+            RbfKernel::ThinPlate => if r > zero { r * r * r.ln() } else { zero },
+            RbfKernel::Polyharmonic(k) => {
+                if k % 2 == 1 {
+                    r.powi(k as i32)
+                } else if r > zero {
+                    r.powi(k as i32) * r.ln()
+                } else {
+                    zero
+                }
+            },
+        }
+    }
+
+    /// lowest degree of the polynomial tail making the system uniquely solvable
+    pub fn min_degree(&self) -> Option<usize> {
+        match *self {
+            RbfKernel::Gaussian(_) | RbfKernel::InverseMultiquadric(_) => None,
+            RbfKernel::Multiquadric(_) => Some(0),
+            RbfKernel::ThinPlate => Some(1),
+            RbfKernel::Polyharmonic(k) => Some(k / 2),
+        }
+    }
+}
+
+/// Radial basis function interpolation s(p) = sum_i w_i phi(|p - p_i|) + polynomial(p)
+/// of the values at the rows of `points`
+pub struct Rbf<F: Float + Lapack> {
+    pub points: Array2<F>,
+    pub kernel: RbfKernel<F>,
+    pub weights: Array1<F>,
+    /// exponents and coefficients of the monomials of the polynomial tail
+    pub exponents: Vec<Vec<usize>>,
+    pub poly: Array1<F>,
+}
+
+impl<F: Float + Lapack> Rbf<F> {
+    /// polynomial tail of `kernel.min_degree()`
+    pub fn new(points: &Array2<F>, values: &Array1<F>, kernel: RbfKernel<F>) -> Result<Self, LinalgError> {
+        Self::with_degree(points, values, kernel, kernel.min_degree())
+    }
+
+    /// Err if the LAPACK solve fails, e.g. on duplicate points
+    pub fn with_degree(points: &Array2<F>, values: &Array1<F>, kernel: RbfKernel<F>, degree: Option<usize>) -> Result<Self, LinalgError> {
+        let (n, dim) = points.dim();
+        if n != values.len() {
+            panic!("points and values have different number of elements!");
+        }
+        let exponents = match degree {
+            Some(deg) => monomials(dim, deg),
+            None => Vec::new(),
+        };
+        let m = exponents.len();
+        if n < m {
+            panic!("too few points for the polynomial tail!");
+        }
+        // the points should determine the polynomial tail uniquely,
+        // i.e. the monomials at the points have full column rank
+        if m > 0 {
+            let p = Array2::from_shape_fn((n, m), |(i, k)| monomial(points.row(i), &exponents[k]));
+            let (_, sv, _) = p.svd(false, false)?;
+            let sv: Vec<F> = sv.iter().map(|&v| <F as NumCast>::from(v).unwrap()).collect();
+            let tol = F::from(n).unwrap() * F::epsilon() * sv[0];
+            if !(sv[m-1] > tol) {
+                panic!("points are degenerate for the polynomial tail!");
+            }
+        }
+        let mut a: Array2<F> = Array2::zeros((n + m, n + m));
+        let mut b: Array1<F> = Array1::zeros(n + m);
+        for i in 0..n {
+            for j in 0..n {
+                a[[i, j]] = kernel.calc(distance(points.row(i), points.row(j)));
+            }
+            for (k, e) in exponents.iter().enumerate() {
+                let v = monomial(points.row(i), e);
+                a[[i, n + k]] = v;
+                a[[n + k, i]] = v;
+            }
+            b[i] = values[i];
+        }
+        let c = a.solve(&b)?;
+        if !c.iter().all(|v| v.is_finite()) {
+            panic!("points are degenerate for the kernel!");
+        }
+        let weights = c.slice(s![..n]).to_owned();
+        let poly = c.slice(s![n..]).to_owned();
+        Ok(Self { points: points.clone(), kernel, weights, exponents, poly })
+    }
+
+    pub fn calc(&self, p: &[F]) -> F {
+        let p = ArrayView1::from(p);
+        let zero: F = Zero::zero();
+        let s = self.points.outer_iter().zip(self.weights.iter())
+            .fold(zero, |m, (pi, &wi)| m + wi * self.kernel.calc(distance(p, pi)));
+        self.exponents.iter().zip(self.poly.iter()).fold(s, |m, (e, &c)| m + c * monomial(p, e))
+    }
+
+    /// calc at the rows of q
+    pub fn calc_array(&self, q: &Array2<F>) -> Array1<F> {
+        q.outer_iter().map(|p| self.calc(&p.to_vec())).collect()
+    }
+}
+
+/// Shepard's inverse distance weighting, sum_i d_i^-power f_i / sum_i d_i^-power
+pub struct Shepard<F: Float> {
+    pub points: Array2<F>,
+    pub values: Array1<F>,
+    pub power: F,
+}
+
+impl<F: Float> Shepard<F> {
+    pub fn new(points: &Array2<F>, values: &Array1<F>, power: F) -> Self {
+        if points.nrows() != values.len() {
+            panic!("points and values have different number of elements!");
+        }
+        if points.nrows() == 0 {
+            panic!("points should not be empty!");
+        }
+        Self { points: points.clone(), values: values.clone(), power }
+    }
+
+    pub fn calc(&self, p: &[F]) -> F {
+        let zero: F = Zero::zero();
+        let p = ArrayView1::from(p);
+        let mut numer = zero;
+        let mut denom = zero;
+        for (pi, &fi) in self.points.outer_iter().zip(self.values.iter()) {
+            let d = distance(p, pi);
+            if d == zero {
+                return fi;
+            }
+            let w = d.powf(-self.power);
+            numer = numer + w * fi;
+            denom = denom + w;
+        }
+        numer / denom
+    }
+
+    /// calc at the rows of q
+    pub fn calc_array(&self, q: &Array2<F>) -> Array1<F> {
+        q.outer_iter().map(|p| self.calc(&p.to_vec())).collect()
+    }
+}
+
+/// Delaunay triangulation (Bowyer-Watson) of 2-D points, interpolated linearly
+/// on the triangles or by Sibson's natural neighbour coordinates
+pub struct Delaunay<F: Float> {
+    pub points: Vec<(F, F)>,
+    pub values: Vec<F>,
+    /// counterclockwise triangles
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl<F: Float> Delaunay<F> {
+    pub fn new(points: &Array2<F>, values: &Array1<F>) -> Self {
+        let zero: F = Zero::zero();
+        let n = points.nrows();
+        if points.ncols() != 2 {
+            panic!("points should be 2-D!");
+        }
+        if n != values.len() {
+            panic!("points and values have different number of elements!");
+        }
+        if n < 3 {
+            panic!("at least 3 points are required!");
+        }
+        let mut p: Vec<(F, F)> = points.outer_iter().map(|r| (r[0], r[1])).collect();
+        // super triangle enclosing every point
+        let (x0, y0, x1, y1) = p.iter().fold((p[0].0, p[0].1, p[0].0, p[0].1), |m, &(x, y)| {
+            (m.0.min(x), m.1.min(y), m.2.max(x), m.3.max(y))
+        });
+        let c = ((x0 + x1) / F::from(2).unwrap(), (y0 + y1) / F::from(2).unwrap());
+        let r = ((x1 - x0).max(y1 - y0)).max(F::epsilon()) * F::from(20).unwrap();
+        p.push((c.0 - r, c.1 - r));
+        p.push((c.0 + r, c.1 - r));
+        p.push((c.0, c.1 + r));
+        let mut triangles: Vec<[usize; 3]> = vec![[n, n + 1, n + 2]];
+        for i in 0..n {
+            if (0..i).any(|j| p[j] == p[i]) {
+                panic!("points should not contain duplicates!");
+            }
+            let (bad, good): (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles.into_iter()
+                .partition(|t| in_circumcircle(p[t[0]], p[t[1]], p[t[2]], p[i]));
+            let mut edges: Vec<(usize, usize)> = Vec::new();
+            for t in bad.iter() {
+                for k in 0..3 {
+                    let e = (t[k], t[(k + 1) % 3]);
+                    let shared = bad.iter().any(|s| s != t && (0..3).any(|l| (s[l], s[(l + 1) % 3]) == (e.1, e.0)));
+                    if !shared {
+                        edges.push(e);
+                    }
+                }
+            }
+            triangles = good;
+            for (a, b) in edges.into_iter() {
+                if orient(p[a], p[b], p[i]) > zero {
+                    triangles.push([a, b, i]);
+                }
+            }
+        }
+        triangles.retain(|t| t.iter().all(|&k| k < n));
+        p.truncate(n);
+        Self { points: p, values: values.to_vec(), triangles }
+    }
+
+    /// linear interpolation on the triangle containing (x, y)
+    pub fn calc(&self, x: F, y: F) -> Result<F, &str> {
+        let q = (x, y);
+        for t in self.triangles.iter() {
+            if let Some(l) = barycentric(self.points[t[0]], self.points[t[1]], self.points[t[2]], q) {
+                return Ok((0..3).fold(F::zero(), |m, k| m + l[k] * self.values[t[k]]));
+            }
+        }
+        Err("out of range!")
+    }
+
+    /// Sibson's natural neighbour interpolation (Watson's algorithm)
+    pub fn calc_natural(&self, x: F, y: F) -> Result<F, &str> {
+        let zero: F = Zero::zero();
+        let q = (x, y);
+        if let Some(i) = self.points.iter().position(|&p| p == q) {
+            return Ok(self.values[i]);
+        }
+        let linear = self.calc(x, y)?;
+        let p = &self.points;
+        let mut w: Vec<(usize, F)> = Vec::new();
+        for t in self.triangles.iter() {
+            if !in_circumcircle(p[t[0]], p[t[1]], p[t[2]], q) {
+                continue;
+            }
+            let c = circumcenter(p[t[0]], p[t[1]], p[t[2]]);
+            for k in 0..3 {
+                let (v, a, b) = (t[k], t[(k + 1) % 3], t[(k + 2) % 3]);
+                let ga = circumcenter(q, p[v], p[a]);
+                let gb = circumcenter(q, p[b], p[v]);
+                let area = orient(ga, c, gb);
+                match w.iter_mut().find(|e| e.0 == v) {
+                    Some(e) => e.1 = e.1 + area,
+                    None => w.push((v, area)),
+                }
+            }
+        }
+        let total = w.iter().fold(zero, |m, e| m + e.1);
+        let ans = w.iter().fold(zero, |m, e| m + e.1 * self.values[e.0]) / total;
+        // q on a triangle edge makes the circumcenters degenerate
+        if ans.is_finite() { Ok(ans) } else { Ok(linear) }
+    }
+}
+
+fn distance<F: Float>(p: ArrayView1<F>, q: ArrayView1<F>) -> F {
+    p.iter().zip(q.iter()).fold(F::zero(), |m, (&a, &b)| m + (a - b) * (a - b)).sqrt()
+}
+
+/// exponents of the monomials in dim variables of total degree <= deg
+fn monomials(dim: usize, deg: usize) -> Vec<Vec<usize>> {
+    if dim == 0 {
+        return vec![Vec::new()];
+    }
+    let mut ret = Vec::new();
+    for d in 0..deg+1 {
+        for mut e in monomials(dim - 1, deg - d).into_iter() {
+            e.insert(0, d);
+            ret.push(e);
+        }
+    }
+    ret.sort_by_key(|e| e.iter().sum::<usize>());
+    ret
+}
+
+fn monomial<F: Float>(p: ArrayView1<F>, e: &Vec<usize>) -> F {
+    p.iter().zip(e.iter()).fold(F::one(), |m, (&x, &k)| m * x.powi(k as i32))
+}
+
+/// twice the signed area of (a, b, c), positive for counterclockwise
+fn orient<F: Float>(a: (F, F), b: (F, F), c: (F, F)) -> F {
+    (b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)
+}
+
+fn circumcenter<F: Float>(a: (F, F), b: (F, F), c: (F, F)) -> (F, F) {
+    let d = orient(a, b, c) * F::from(2).unwrap();
+    let (bx, by) = (b.0 - a.0, b.1 - a.1);
+    let (cx, cy) = (c.0 - a.0, c.1 - a.1);
+    let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+    (a.0 + (cy * b2 - by * c2) / d, a.1 + (bx * c2 - cx * b2) / d)
+}
+
+/// q strictly inside the circumcircle of the counterclockwise triangle (a, b, c)
+fn in_circumcircle<F: Float>(a: (F, F), b: (F, F), c: (F, F), q: (F, F)) -> bool {
+    let (ax, ay) = (a.0 - q.0, a.1 - q.1);
+    let (bx, by) = (b.0 - q.0, b.1 - q.1);
+    let (cx, cy) = (c.0 - q.0, c.1 - q.1);
+    let det = (ax * ax + ay * ay) * (bx * cy - cx * by)
+        - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay);
+    det > F::zero()
+}
+
+/// barycentric coordinates of q if it lies in the triangle (a, b, c)
+fn barycentric<F: Float>(a: (F, F), b: (F, F), c: (F, F), q: (F, F)) -> Option<[F; 3]> {
+    let zero: F = Zero::zero();
+    let eps = F::epsilon() * F::from(16).unwrap();
+    let s = orient(a, b, c);
+    let l = [orient(q, b, c) / s, orient(a, q, c) / s, orient(a, b, q) / s];
+    if l.iter().all(|&li| li >= zero - eps) {
+        Some(l)
+    } else {
+        None
+    }
+}
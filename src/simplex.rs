@@ -0,0 +1,259 @@
+use num_traits::float::Float;
+use num_traits::{Zero, One};
+
+/// Symmetric orbits of the Dunavant rules as (weight, a, b, c).
+/// b == c == a means the centroid, c == b means the 3 permutations of (a, b, b),
+/// otherwise the 6 permutations of (a, b, c).
+const DUNAVANT: [&[(f64, f64, f64, f64)]; 8] = [
+    &[(1.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0)],
+    &[(1.0 / 3.0, 2.0 / 3.0, 1.0 / 6.0, 1.0 / 6.0)],
+    &[(-27.0 / 48.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+      (25.0 / 48.0, 0.6, 0.2, 0.2)],
+    &[(0.223381589678011, 0.108103018168070, 0.445948490915965, 0.445948490915965),
+      (0.109951743655322, 0.816847572980459, 0.091576213509771, 0.091576213509771)],
+    &[(0.225, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+      (0.132394152788506, 0.059715871789770, 0.470142064105115, 0.470142064105115),
+      (0.125939180544827, 0.797426985353087, 0.101286507323456, 0.101286507323456)],
+    &[(0.116786275726379, 0.501426509658179, 0.249286745170910, 0.249286745170910),
+      (0.050844906370207, 0.873821971016996, 0.063089014491502, 0.063089014491502),
+      (0.082851075618374, 0.053145049844817, 0.310352451033784, 0.636502499121399)],
+    &[(-0.149570044467682, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+      (0.175615257433208, 0.479308067841920, 0.260345966079040, 0.260345966079040),
+      (0.053347235608838, 0.869739794195568, 0.065130102902216, 0.065130102902216),
+      (0.077113760890257, 0.048690315425316, 0.312865496004874, 0.638444188569810)],
+    &[(0.144315607677787, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+      (0.095091634267285, 0.081414823414554, 0.459292588292723, 0.459292588292723),
+      (0.103217370534718, 0.658861384496480, 0.170569307751760, 0.170569307751760),
+      (0.032458497623198, 0.898905543365938, 0.050547228317031, 0.050547228317031),
+      (0.027230314174435, 0.008394777409958, 0.263112829634638, 0.728492392955404)],
+];
+
+/// Integral of f(x, y) over triangles and simple polygons
+pub struct TriIntegral<F: Float> {
+    pub rhs: fn(F, F) -> F,
+}
+
+impl<F: Float> TriIntegral<F> {
+    pub fn new(rhs: fn(F, F) -> F) -> Self {
+        Self { rhs }
+    }
+
+    /// Dunavant rule exact for polynomials of total degree `degree` (1 to 8)
+    pub fn dunavant(&self, p: [(F, F); 3], degree: usize) -> F {
+        let (points, weights) = dunavant_rule(degree);
+        self.apply(p, &points, &weights)
+    }
+
+    /// Grundmann-Moller rule exact for polynomials of total degree 2s+1
+    pub fn grundmann_moller(&self, p: [(F, F); 3], s: usize) -> F {
+        let (points, weights) = grundmann_moller_rule(2, s);
+        self.apply(p, &points, &weights)
+    }
+
+    /// Dunavant rule applied to every triangle of an ear-clipping triangulation
+    pub fn polygon(&self, vertices: &Vec<(F, F)>, degree: usize) -> F {
+        let zero: F = Zero::zero();
+        let (points, weights) = dunavant_rule(degree);
+        triangulate(vertices).iter().map(|t| {
+            let p = [vertices[t[0]], vertices[t[1]], vertices[t[2]]];
+            self.apply(p, &points, &weights)
+        }).fold(zero, |m, i| m + i)
+    }
+
+    fn apply(&self, p: [(F, F); 3], points: &Vec<Vec<F>>, weights: &Vec<F>) -> F {
+        let zero: F = Zero::zero();
+        let s = points.iter().zip(weights.iter()).map(|(l, &w)| {
+            let x = l[0] * p[0].0 + l[1] * p[1].0 + l[2] * p[2].0;
+            let y = l[0] * p[0].1 + l[1] * p[1].1 + l[2] * p[2].1;
+            w * (self.rhs)(x, y)
+        }).fold(zero, |m, i| m + i);
+        s * triangle_area(p[0], p[1], p[2])
+    }
+}
+
+/// Integral of f(x, y, z) over tetrahedra
+pub struct TetIntegral<F: Float> {
+    pub rhs: fn(F, F, F) -> F,
+}
+
+impl<F: Float> TetIntegral<F> {
+    pub fn new(rhs: fn(F, F, F) -> F) -> Self {
+        Self { rhs }
+    }
+
+    /// Symmetric rule exact for polynomials of total degree `degree` (1 to 3)
+    pub fn symmetric(&self, p: [(F, F, F); 4], degree: usize) -> F {
+        let (points, weights) = tetrahedron_rule(degree);
+        self.apply(p, &points, &weights)
+    }
+
+    /// Grundmann-Moller rule exact for polynomials of total degree 2s+1
+    pub fn grundmann_moller(&self, p: [(F, F, F); 4], s: usize) -> F {
+        let (points, weights) = grundmann_moller_rule(3, s);
+        self.apply(p, &points, &weights)
+    }
+
+    fn apply(&self, p: [(F, F, F); 4], points: &Vec<Vec<F>>, weights: &Vec<F>) -> F {
+        let zero: F = Zero::zero();
+        let s = points.iter().zip(weights.iter()).map(|(l, &w)| {
+            let x = (0..4).map(|i| l[i] * p[i].0).fold(zero, |m, j| m + j);
+            let y = (0..4).map(|i| l[i] * p[i].1).fold(zero, |m, j| m + j);
+            let z = (0..4).map(|i| l[i] * p[i].2).fold(zero, |m, j| m + j);
+            w * (self.rhs)(x, y, z)
+        }).fold(zero, |m, i| m + i);
+        s * tetrahedron_volume(p[0], p[1], p[2], p[3])
+    }
+}
+
+pub fn triangle_area<F: Float>(p0: (F, F), p1: (F, F), p2: (F, F)) -> F {
+    let one: F = One::one();
+    let two: F = one + one;
+    ((p1.0 - p0.0) * (p2.1 - p0.1) - (p2.0 - p0.0) * (p1.1 - p0.1)).abs() / two
+}
+
+pub fn tetrahedron_volume<F: Float>(p0: (F, F, F), p1: (F, F, F), p2: (F, F, F), p3: (F, F, F)) -> F {
+    let six = F::from(6).unwrap();
+    let a = (p1.0 - p0.0, p1.1 - p0.1, p1.2 - p0.2);
+    let b = (p2.0 - p0.0, p2.1 - p0.1, p2.2 - p0.2);
+    let c = (p3.0 - p0.0, p3.1 - p0.1, p3.2 - p0.2);
+    let det = a.0 * (b.1 * c.2 - b.2 * c.1) - a.1 * (b.0 * c.2 - b.2 * c.0) + a.2 * (b.0 * c.1 - b.1 * c.0);
+    det.abs() / six
+}
+
+/// Barycentric points and weights (summing to 1) of the Dunavant rule on the triangle
+pub fn dunavant_rule<F: Float>(degree: usize) -> (Vec<Vec<F>>, Vec<F>) {
+    if degree == 0 || degree > DUNAVANT.len() {
+        panic!("degree of Dunavant rule should be 1 to 8!");
+    }
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    for &(w, a, b, c) in DUNAVANT[degree - 1].iter() {
+        let orbit = if a == b && b == c {
+            vec![[a, b, c]]
+        } else if b == c {
+            vec![[a, b, b], [b, a, b], [b, b, a]]
+        } else {
+            vec![[a, b, c], [a, c, b], [b, a, c], [b, c, a], [c, a, b], [c, b, a]]
+        };
+        for l in orbit.iter() {
+            points.push(l.iter().map(|&li| F::from(li).unwrap()).collect());
+            weights.push(F::from(w).unwrap());
+        }
+    }
+    (points, weights)
+}
+
+/// Barycentric points and weights (summing to 1) of symmetric rules on the tetrahedron
+pub fn tetrahedron_rule<F: Float>(degree: usize) -> (Vec<Vec<F>>, Vec<F>) {
+    let one: F = One::one();
+    let four = F::from(4).unwrap();
+    let orbit = |a: F, b: F| (0..4).map(|i| (0..4).map(|j| if i == j { a } else { b }).collect()).collect::<Vec<Vec<F>>>();
+    match degree {
+        1 => (vec![vec![one / four; 4]], vec![one]),
+        2 => {
+            let a = F::from(0.5854101966249685).unwrap();
+            let b = F::from(0.1381966011250105).unwrap();
+            (orbit(a, b), vec![one / four; 4])
+        },
+        3 => {
+            let half = one / (one + one);
+            let sixth = one / F::from(6).unwrap();
+            let mut points = vec![vec![one / four; 4]];
+            points.append(&mut orbit(half, sixth));
+            let mut weights = vec![F::from(-0.8).unwrap()];
+            weights.append(&mut vec![F::from(0.45).unwrap(); 4]);
+            (points, weights)
+        },
+        _ => panic!("degree of tetrahedron rule should be 1 to 3!"),
+    }
+}
+
+/// Barycentric points and weights (summing to 1) of the Grundmann-Moller rule
+/// of degree 2s+1 on the `dim`-simplex
+pub fn grundmann_moller_rule<F: Float>(dim: usize, s: usize) -> (Vec<Vec<F>>, Vec<F>) {
+    let one: F = One::one();
+    let d = 2 * s + 1;
+    let fact = |k: usize| (1..k+1).fold(one, |m, i| m * F::from(i).unwrap());
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    for i in 0..s+1 {
+        let denom = F::from(d + dim - 2 * i).unwrap();
+        let sign = if i % 2 == 0 { one } else { -one };
+        let w = sign * denom.powi(d as i32) * fact(dim) / (F::from(2).unwrap().powi(2 * s as i32) * fact(i) * fact(d + dim - i));
+        for beta in compositions(s - i, dim + 1).iter() {
+            points.push(beta.iter().map(|&bj| F::from(2 * bj + 1).unwrap() / denom).collect());
+            weights.push(w);
+        }
+    }
+    (points, weights)
+}
+
+/// all ways to write `total` as an ordered sum of `parts` non-negative integers
+fn compositions(total: usize, parts: usize) -> Vec<Vec<usize>> {
+    if parts == 1 {
+        return vec![vec![total]];
+    }
+    let mut ret = Vec::new();
+    for first in 0..total+1 {
+        for mut rest in compositions(total - first, parts - 1).into_iter() {
+            rest.insert(0, first);
+            ret.push(rest);
+        }
+    }
+    ret
+}
+
+/// Ear-clipping triangulation of a simple polygon given in either orientation
+pub fn triangulate<F: Float>(vertices: &Vec<(F, F)>) -> Vec<[usize; 3]> {
+    let zero: F = Zero::zero();
+    let n = vertices.len();
+    if n < 3 {
+        panic!("polygon should have at least 3 vertices!");
+    }
+    let cross = |i: usize, j: usize, k: usize| {
+        let (a, b, c) = (vertices[i], vertices[j], vertices[k]);
+        (b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)
+    };
+    let area2 = (0..n).map(|i| {
+        let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+        a.0 * b.1 - b.0 * a.1
+    }).fold(zero, |m, j| m + j);
+    let mut idx: Vec<usize> = (0..n).collect();
+    if area2 < zero {
+        idx.reverse();
+    }
+    let mut tri = Vec::with_capacity(n - 2);
+    while idx.len() > 3 {
+        let m = idx.len();
+        let mut clipped = false;
+        for j in 0..m {
+            let (a, b, c) = (idx[(j + m - 1) % m], idx[j], idx[(j + 1) % m]);
+            let turn = cross(a, b, c);
+            if turn == zero {
+                idx.remove(j);
+                clipped = true;
+                break;
+            }
+            if turn < zero {
+                continue;
+            }
+            let inside = idx.iter().any(|&p| {
+                p != a && p != b && p != c
+                    && cross(a, b, p) >= zero && cross(b, c, p) >= zero && cross(c, a, p) >= zero
+            });
+            if !inside {
+                tri.push([a, b, c]);
+                idx.remove(j);
+                clipped = true;
+                break;
+            }
+        }
+        if !clipped {
+            panic!("polygon should be simple!");
+        }
+    }
+    if cross(idx[0], idx[1], idx[2]) != zero {
+        tri.push([idx[0], idx[1], idx[2]]);
+    }
+    tri
+}
@@ -0,0 +1,114 @@
+use num_traits::float::{Float, FloatConst};
+use num_traits::{Zero, One};
+
+/// ln(Gamma(x)) by the Lanczos approximation (g = 7, 9 terms), with the reflection formula for x < 1/2
+pub fn ln_gamma<F: Float + FloatConst>(x: F) -> F {
+    let one: F = One::one();
+    let half: F = F::from(0.5).unwrap();
+    if x < half {
+        // Gamma(x) Gamma(1 - x) = pi / sin(pi x)
+        return (F::PI() / (F::PI() * x).sin().abs()).ln() - ln_gamma(one - x);
+    }
+    let c: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - one;
+    let a = (1..9).fold(F::from(c[0]).unwrap(), |m, i| m + F::from(c[i]).unwrap() / (x + F::from(i).unwrap()));
+    let t = x + F::from(7.5).unwrap();
+    half * (F::PI() + F::PI()).ln() + (x + half) * t.ln() - t + a.ln()
+}
+
+/// regularized incomplete beta function I_x(a, b) by the continued fraction (modified Lentz)
+pub fn inc_beta<F: Float + FloatConst>(a: F, b: F, x: F) -> F {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    if !(a > zero && b > zero) {
+        panic!("a and b should be positive!");
+    }
+    if !(x >= zero && x <= one) {
+        panic!("x should be in [0, 1]!");
+    }
+    if x == zero || x == one {
+        return x;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (one - x).ln()).exp();
+    // the continued fraction converges fast for x < (a + 1) / (a + b + 2)
+    if x > (a + one) / (a + b + one + one) {
+        return one - inc_beta(b, a, one - x);
+    }
+    let tiny = F::min_positive_value() / F::epsilon();
+    let clamp = |v: F| if v.abs() < tiny { tiny } else { v };
+    let mut c = one;
+    let mut d = one / clamp(one - (a + b) * x / (a + one));
+    let mut f = d;
+    for m in 1..500 {
+        let mf = F::from(m).unwrap();
+        let a2 = a + mf + mf;
+        // even and odd steps of the fraction
+        let e = mf * (b - mf) * x / ((a2 - one) * a2);
+        d = one / clamp(one + e * d);
+        c = clamp(one + e / c);
+        f = f * c * d;
+        let o = -(a + mf) * (a + b + mf) * x / (a2 * (a2 + one));
+        d = one / clamp(one + o * d);
+        c = clamp(one + o / c);
+        let delta = c * d;
+        f = f * delta;
+        if (delta - one).abs() < F::epsilon() {
+            break;
+        }
+    }
+    front * f / a
+}
+
+/// cumulative distribution function of Student's t distribution with nu degrees of freedom
+pub fn student_t_cdf<F: Float + FloatConst>(t: F, nu: F) -> F {
+    let one: F = One::one();
+    let half: F = F::from(0.5).unwrap();
+    if t.is_infinite() {
+        return if t > F::zero() { one } else { F::zero() };
+    }
+    let tail = half * inc_beta(half * nu, half, nu / (nu + t * t));
+    if t > F::zero() { one - tail } else { tail }
+}
+
+/// quantile of Student's t distribution with nu degrees of freedom, by bisection on the cdf
+pub fn student_t_quantile<F: Float + FloatConst>(p: F, nu: F) -> F {
+    let zero: F = Zero::zero();
+    let one: F = One::one();
+    let half: F = F::from(0.5).unwrap();
+    if !(p > zero && p < one) {
+        panic!("p should be in (0, 1)!");
+    }
+    if !(nu > zero) {
+        panic!("nu should be positive!");
+    }
+    if p < half {
+        return -student_t_quantile(one - p, nu);
+    }
+    let mut hi = one;
+    while student_t_cdf(hi, nu) < p {
+        hi = hi + hi;
+    }
+    let mut lo = zero;
+    for _ in 0..200 {
+        let mid = half * (lo + hi);
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        if student_t_cdf(mid, nu) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    half * (lo + hi)
+}
@@ -0,0 +1,72 @@
+use num_traits::float::{Float, FloatConst};
+use num_traits::Zero;
+use crate::integral::*;
+
+/// \int_a^b \int_{g1(x)}^{g2(x)} \int_{h1(x, y)}^{h2(x, y)} f(x, y, z) dz dy dx
+pub struct TIntegral<F: Float + FloatConst> {
+    pub rhs: fn(F, F, F) -> F,
+}
+
+impl<F: Float + FloatConst> TIntegral<F> {
+    pub fn new(rhs: fn(F, F, F) -> F) -> Self {
+        Self { rhs }
+    }
+
+    pub fn trapezoid(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), n: usize) -> F {
+        let (a1, b1) = ab1;
+        trapezoid_rule(|x: F| {
+            trapezoid_rule(|y: F| {
+                trapezoid_rule(|z: F| (self.rhs)(x, y, z), (ab3.0)(x, y), (ab3.1)(x, y), n)
+            }, (ab2.0)(x), (ab2.1)(x), n)
+        }, a1, b1, n)
+    }
+
+    pub fn simpson(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), n: usize) -> F {
+        let (a1, b1) = ab1;
+        simpson_rule(|x: F| {
+            simpson_rule(|y: F| {
+                simpson_rule(|z: F| (self.rhs)(x, y, z), (ab3.0)(x, y), (ab3.1)(x, y), n)
+            }, (ab2.0)(x), (ab2.1)(x), n)
+        }, a1, b1, n)
+    }
+
+    pub fn gauss_legendre(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), n: usize) -> F {
+        let (t, w) = legendre_nodes(n);
+        self.nested(ab1, ab2, ab3, &t, &w)
+    }
+
+    pub fn chebyshev(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), n: usize) -> F {
+        let (t, w) = chebyshev_nodes(n);
+        self.nested(ab1, ab2, ab3, &t, &w)
+    }
+
+    pub fn def(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), n: usize, h: F) -> F {
+        let (a1, b1) = ab1;
+        def_rule(|x: F| {
+            def_rule(|y: F| {
+                def_rule(|z: F| (self.rhs)(x, y, z), (ab3.0)(x, y), (ab3.1)(x, y), n, h)
+            }, (ab2.0)(x), (ab2.1)(x), n, h)
+        }, a1, b1, n, h)
+    }
+
+    /// nested adaptive Gauss-Kronrod rule with tolerances (x, y, z),
+    /// returns (integral, error estimate including the propagated inner errors)
+    pub fn gauss_kronrod(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), tol: (F, F, F)) -> (F, F) {
+        let (a1, b1) = ab1;
+        adaptive_gk(|x: F| {
+            adaptive_gk(|y: F| {
+                adaptive_gk(|z: F| ((self.rhs)(x, y, z), Zero::zero()), (ab3.0)(x, y), (ab3.1)(x, y), tol.2)
+            }, (ab2.0)(x), (ab2.1)(x), tol.1)
+        }, a1, b1, tol.0)
+    }
+
+    /// the rule of the nodes t and weights w in each direction
+    fn nested(&self, ab1: (F, F), ab2: (fn(F)->F, fn(F)->F), ab3: (fn(F, F)->F, fn(F, F)->F), t: &Vec<F>, w: &Vec<F>) -> F {
+        let (a1, b1) = ab1;
+        quadrature(|x: F| {
+            quadrature(|y: F| {
+                quadrature(|z: F| (self.rhs)(x, y, z), (ab3.0)(x, y), (ab3.1)(x, y), t, w)
+            }, (ab2.0)(x), (ab2.1)(x), t, w)
+        }, a1, b1, t, w)
+    }
+}